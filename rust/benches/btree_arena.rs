@@ -0,0 +1,103 @@
+//!
+//! Lookup throughput of the boxed [BTree] against the arena backed [ArenaTree].
+//!
+//! Both trees get the same keys inserted in the same (shuffled) order with `insert_balanced`, so
+//! they end up exactly the same shape. The only difference left is where the nodes sit in memory.
+//!
+//! Run it with:
+//!
+//! ```text
+//! cargo bench --bench btree_arena
+//! ```
+//!
+//! Pass a number of keys to try a different size, e.g. `cargo bench --bench btree_arena -- 100000`.
+//!
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use exercises::btree::{ArenaTree, BTree};
+use rand::prelude::*;
+
+const DEFAULT_KEYS: u32 = 1_000_000;
+
+fn main() {
+    // `cargo bench` passes `--bench` along, skip anything that isn't a number.
+    let keys = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_KEYS);
+
+    let mut rng = thread_rng();
+
+    // Even numbers go in the tree, so looking up the odd ones gives us misses.
+    let mut values = (0..keys).map(|key| key * 2).collect::<Vec<u32>>();
+    values.shuffle(&mut rng);
+
+    let lookups = (0..keys).map(|_| rng.gen_range(0..keys * 2)).collect::<Vec<u32>>();
+
+    println!("{} keys, {} lookups", keys, lookups.len());
+
+    let (boxed, build) = time(|| {
+        let mut tree = BTree::empty();
+        for &val in values.iter() {
+            tree.insert_balanced(val);
+        }
+        tree
+    });
+    report("boxed build", keys as usize, build);
+
+    let (arena, build) = time(|| {
+        let mut tree = ArenaTree::with_capacity(values.len());
+        for &val in values.iter() {
+            tree.insert_balanced(val);
+        }
+        tree
+    });
+    report("arena build", keys as usize, build);
+
+    assert_eq!(boxed.depth(), arena.depth());
+
+    // A few rounds each, taking the best, to smooth out noise from the rest of the machine.
+    let mut best_boxed = Duration::MAX;
+    let mut best_arena = Duration::MAX;
+    for round in 1..=3 {
+        let (boxed_hits, boxed_time) = time(|| {
+            lookups.iter().filter(|val| boxed.contains(black_box(val))).count()
+        });
+        let (arena_hits, arena_time) = time(|| {
+            lookups.iter().filter(|val| arena.contains(black_box(val))).count()
+        });
+
+        assert_eq!(boxed_hits, arena_hits);
+
+        println!("round {}:", round);
+        report("  boxed lookup", lookups.len(), boxed_time);
+        report("  arena lookup", lookups.len(), arena_time);
+
+        best_boxed = best_boxed.min(boxed_time);
+        best_arena = best_arena.min(arena_time);
+    }
+
+    println!("best of 3:");
+    report("  boxed lookup", lookups.len(), best_boxed);
+    report("  arena lookup", lookups.len(), best_arena);
+    println!(
+        "  arena is {:.2}x the speed of boxed",
+        best_boxed.as_secs_f64() / best_arena.as_secs_f64()
+    );
+}
+
+fn time<R, F: FnOnce() -> R>(f: F) -> (R, Duration) {
+    let start = Instant::now();
+    let result = black_box(f());
+    (result, start.elapsed())
+}
+
+fn report(name: &str, count: usize, elapsed: Duration) {
+    println!(
+        "{:>16}: {:>8.1} ms, {:>6.2} M/s",
+        name,
+        elapsed.as_secs_f64() * 1000.0,
+        count as f64 / elapsed.as_secs_f64() / 1_000_000.0
+    );
+}
@@ -1,17 +1,99 @@
-use exercises;
+use exercises::btree::{BTree, CursorMut};
 use text_io::read;
 
 fn main() {
-    println!("Creating a binary tree");
-    println!("Please enter an integer");
-    let input: isize = read!();
-    let mut the_tree = exercises::btree::BTree::new(input);
+    // Pass a tree saved from an earlier run (the "Saved:" line) to carry on where you left off.
+    let mut the_tree = match std::env::args().nth(1) {
+        Some(text) => {
+            println!("Loading a binary tree");
+            match text.parse::<BTree<isize>>() {
+                Ok(tree) => tree,
+                Err(err) => {
+                    eprintln!("Couldn't load the tree: {}", err);
+                    std::process::exit(1);
+                },
+            }
+        },
+        None => {
+            println!("Creating a binary tree");
+            BTree::empty()
+        },
+    };
     println!("Press Ctrl+C to quit modifying tree");
     loop {
-        println!("{:#?}", the_tree);
-        println!("Depth: {} | Balanced: {}", the_tree.depth(), the_tree.balanced());
-        println!("Please enter an integer to add to the tree");
-        let input: isize = read!();
-        the_tree.insert(input);
+        println!("Please enter an integer to add to the tree, or 'cursor' to step through it");
+        let input: String = read!();
+        if input.is_empty() {
+            // Out of input.
+            return;
+        } else if input == "cursor" {
+            step_through(&mut the_tree);
+        } else {
+            match input.parse::<isize>() {
+                Ok(val) => the_tree.insert(val),
+                Err(_) => {
+                    println!("'{}' isn't an integer", input);
+                    continue;
+                },
+            }
+        }
+        println!("{}", the_tree);
+        println!(
+            "Length: {} | Depth: {} | Balanced: {}",
+            the_tree.len(),
+            the_tree.depth(),
+            the_tree.balanced()
+        );
+        println!("Saved: {}", the_tree.to_text());
+    }
+}
+
+/// Walk around the tree one node at a time with a cursor, editing as we go.
+///
+/// The tree is taken apart while the cursor is out, so we can only show where the cursor is, the
+/// whole tree gets printed again once we're done.
+fn step_through(tree: &mut BTree<isize>) {
+    let mut cursor = tree.cursor_mut();
+    println!("Commands: left, right, parent, next, prev, insert <integer>, remove, done");
+    loop {
+        show(&cursor);
+        let command: String = read!();
+        let moved = match command.as_str() {
+            "left" => cursor.move_left(),
+            "right" => cursor.move_right(),
+            "parent" => cursor.move_parent(),
+            "next" => cursor.move_next(),
+            "prev" => cursor.move_prev(),
+            "insert" => {
+                let val: isize = read!();
+                if !cursor.insert(val) {
+                    println!("{} is already in the tree, or doesn't belong under here", val);
+                }
+                true
+            },
+            "remove" => match cursor.remove() {
+                Some(val) => {
+                    println!("Removed {}", val);
+                    true
+                },
+                None => false,
+            },
+            "done" | "" => return,
+            _ => {
+                println!("Unknown command '{}'", command);
+                true
+            },
+        };
+        if !moved {
+            println!("Can't go that way");
+        }
+    }
+}
+
+fn show(cursor: &CursorMut<'_, isize>) {
+    match cursor.current() {
+        Some(val) if cursor.is_root() => println!("At: {} (the root)", val),
+        Some(val) => println!("At: {}", val),
+        None => println!("At: nothing, the tree is empty"),
     }
 }
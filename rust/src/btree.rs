@@ -1,3 +1,37 @@
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+
+mod arena;
+mod cursor;
+mod display;
+mod dot;
+mod iter;
+mod map;
+mod multiset;
+mod multiway;
+mod persistent;
+mod red_black;
+mod serialize;
+mod set;
+mod splay;
+mod split;
+mod validate;
+
+pub use arena::{
+    ArenaIntoIter, ArenaIter, ArenaLevelOrder, ArenaPostOrder, ArenaPreOrder, ArenaRange, ArenaTree,
+};
+pub use cursor::CursorMut;
+pub use display::Layout;
+pub use iter::{IntoIter, Iter, LevelOrder, PostOrder, PreOrder, Range};
+pub use map::{Entry, TreeMap};
+pub use multiset::{Counts, MultisetIter, TreeMultiset};
+pub use multiway::{MultiwayBTree, MultiwayIter};
+pub use persistent::{PersistentIter, PersistentTree};
+pub use red_black::RedBlackTree;
+pub use serialize::ParseError;
+pub use splay::SplayTree;
+pub use validate::{Direction, ValidationError, Violation};
+
 ///
 /// A simple binary tree.
 ///
@@ -37,6 +71,18 @@
 ///
 /// Making the tree very imbalanced and sub-optimal to search.
 ///
+/// The tree holds any type with a total ordering (anything that implements `Ord`), so strings,
+/// tuples, and your own keys work just as well as integers:
+///
+/// ```
+/// # use exercises::btree::BTree;
+/// let mut t = BTree::new("m");
+/// t.insert("a");
+/// t.insert("z");
+///
+/// assert!(t.balanced());
+/// ```
+///
 /// The reason I haven't solved this is because the hoops one needs to jump through in Rust are
 /// trickey to solve the ownership problem when sorting a tree in-place.
 ///
@@ -46,12 +92,60 @@
 /// Some day I will figure out the lifetimes, Rc, Arc, and Boxes required to sort a tree in-place.
 /// For now I'm putting that on the back-burner.
 ///
-#[derive(Debug, PartialEq, Clone)]
-pub struct BTree<'a> {
-    value: isize,
-    left: Option<Box<BTree<'a>>>,
-    right: Option<Box<BTree<'a>>>,
-    _phantom: std::marker::PhantomData<&'a ()>
+/// UPDATE: It turns out you don't need Rc or Arc to balance the tree as you go, just a way to
+/// rotate a node in place. `insert_balanced` does AVL style rotations on the way back up from an
+/// insert, so the same steps above produce the nicely balanced tree instead:
+///
+/// ```
+/// # use exercises::btree::BTree;
+/// let mut t = BTree::new(7);
+/// for val in 1..7 {
+///     t.insert_balanced(val);
+///     assert!(t.balanced());
+/// }
+/// assert_eq!(t.depth(), 3);
+/// assert_eq!(t.to_string(), "4 -> 6 -> 7\n|     `-> 5\n`-> 2 -> 3\n     `-> 1");
+/// ```
+///
+/// (These diagrams are what `Display` prints, see [Layout] for the details and another way to
+/// draw a tree.)
+///
+/// And if you've already built a lopsided tree with `insert`, `balance` will fix it up in place
+/// with one linear pass.
+///
+/// If you have all of the values up front, skip the lopsided tree altogether: `from_sorted` (or
+/// `collect`, which sorts for you) builds a balanced tree straight away.
+///
+/// Arc did turn out to be useful for the other idea, returning a new tree instead of changing
+/// the old one: [PersistentTree] shares every sub-tree a change doesn't touch between the old
+/// and new versions, so keeping old versions around is cheap.
+///
+/// Lopsided trees can get very deep, so nothing here recurses once per level: inserting,
+/// removing, splitting, joining, cloning, comparing, iterating and dropping all keep a stack of
+/// their own instead, and a million node vine is fine. The one exception is the derived `Debug`
+/// (`{:?}`), which still recurses and will overflow the stack on a tree that deep.
+///
+#[derive(Debug)]
+pub struct BTree<T> {
+    root: Option<Box<Node<T>>>,
+    rotations: usize,
+}
+
+///
+/// A single node in a [BTree].
+///
+/// This used to *be* the tree, but a tree made only of nodes always has at least one value in it,
+/// which makes removing the last value impossible.
+/// Now `BTree` is a handle to an optional root node, and all of the recursive work happens on
+/// nodes.
+///
+#[derive(Debug, PartialEq)]
+struct Node<T> {
+    value: T,
+    height: isize,
+    size: usize,
+    left: Option<Box<Node<T>>>,
+    right: Option<Box<Node<T>>>,
 }
 
 #[test]
@@ -61,91 +155,839 @@ fn test_btree() {
     t.insert(1);
     t.insert(3);
 
-    assert_eq!(t.balanced(), true);
+    assert!(t.balanced());
 
     assert_eq!(
-        t,
-        BTree {
+        t.root,
+        Some(Box::new(Node {
             value: 2,
-            left: Some(Box::new(BTree::new(1))),
-            right: Some(Box::new(BTree::new(3))),
-            _phantom: std::marker::PhantomData
-        }
+            height: 2,
+            size: 3,
+            left: Some(Box::new(Node::new(1))),
+            right: Some(Box::new(Node::new(3))),
+        }))
     );
 
     t.insert(4);
     t.insert(5);
 
-    assert_eq!(t.balanced(), false);
+    assert!(!t.balanced());
 
-    // t.balance();
+    t.balance();
 
-    // assert_eq!(t.balanced(), true);
+    assert!(t.balanced());
 }
 
-impl<'a> BTree<'a> {
-    pub fn new(val: isize) -> BTree<'a> {
-        BTree {
-            value: val,
-            left: None,
-            right: None,
-            _phantom: std::marker::PhantomData,
+#[test]
+fn test_btree_insert_balanced() {
+    use rand::prelude::*;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-1000, 1000);
+
+    let mut t = BTree::new(0);
+
+    for _ in 0..1024 {
+        t.insert_balanced(rng.sample(distr));
+
+        assert!(t.balanced());
+    }
+
+    assert!(t.is_height_balanced());
+    assert_eq!(t.validate(), Ok(()));
+
+    // Sorted input is the worst case for `insert`, but should not bother `insert_balanced`.
+    let mut t = BTree::new(0);
+
+    for val in 1..1023 {
+        t.insert_balanced(val);
+    }
+
+    assert_eq!(t.depth(), 10);
+}
+
+#[test]
+fn test_btree_generic() {
+    let mut t = BTree::new(String::from("m"));
+
+    t.insert(String::from("c"));
+    t.insert(String::from("x"));
+    t.insert(String::from("m"));
+
+    assert_eq!(
+        t.root,
+        Some(Box::new(Node {
+            value: String::from("m"),
+            height: 2,
+            size: 3,
+            left: Some(Box::new(Node::new(String::from("c")))),
+            right: Some(Box::new(Node::new(String::from("x")))),
+        }))
+    );
+
+    let mut t = BTree::new((1, 'b'));
+
+    t.insert_balanced((1, 'a'));
+    t.insert_balanced((0, 'z'));
+
+    assert_eq!(t.depth(), 2);
+    assert!(t.balanced());
+}
+
+#[test]
+fn test_btree_balance() {
+    use rand::prelude::*;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-1000, 1000);
+
+    let mut t = BTree::new(0);
+
+    for _ in 0..1024 {
+        t.insert(rng.sample(distr));
+    }
+
+    let before = t.clone();
+
+    t.balance();
+
+    assert!(t.balanced());
+    assert!(t.depth() <= before.depth());
+
+    // Balancing again should not change a thing
+    let once = t.clone();
+    t.balance();
+    assert_eq!(t, once);
+}
+
+#[test]
+fn test_btree_remove() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn in_order(node: &Option<Box<Node<i32>>>, out: &mut Vec<i32>) {
+        if let Some(node) = node {
+            in_order(&node.left, out);
+            out.push(node.value);
+            in_order(&node.right, out);
         }
     }
 
-    /// Insert a value into the tree
-    ///
-    /// ```
-    /// # use exercises::btree::BTree;
-    /// let mut my_tree = BTree::new(5);
-    /// my_tree.insert(3);
-    /// my_tree.insert(7);
-    /// ```
-    pub fn insert(&mut self, val: isize) {
-        // Duplicate values are not stored
-        if val == self.value {
-            return
+    fn check_cached(node: &Option<Box<Node<i32>>>) -> (isize, usize) {
+        match node {
+            None => (0, 0),
+            Some(node) => {
+                let (left_height, left_size) = check_cached(&node.left);
+                let (right_height, right_size) = check_cached(&node.right);
+                assert_eq!(node.height, 1 + std::cmp::max(left_height, right_height));
+                assert_eq!(node.size, 1 + left_size + right_size);
+                (node.height, node.size)
+            },
         }
+    }
 
-        // Credit to this article for helping me break through lifetimes confusion
-        // https://gist.github.com/aidanhs/5ac9088ca0f6bdd4a370
-        if val < self.value {
-            // We match on the mutable value `self.left`
-            match &mut self.left {
-                // Each pattern must also include `&mut`, so we can modify the node.
-                &mut None => *(&mut self.left) = Some(Box::new(BTree::new(val))),
-                // We match on Some(ref ...) so we don't move into the node,
-                // just use a refernece to the node.
-                &mut Some(ref mut node) => node.insert(val),
-            }
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-100, 100);
+
+    let mut plain = BTree::new(0);
+    let mut balanced = BTree::new(0);
+    let mut expected = BTreeSet::new();
+    expected.insert(0);
+
+    for _ in 0..4096 {
+        let val = rng.sample(distr);
+
+        if rng.gen_bool(0.5) {
+            let removed = expected.remove(&val);
+            assert_eq!(plain.remove(&val), removed);
+            assert_eq!(balanced.remove_balanced(&val), removed);
         } else {
-            match &mut self.right {
-                &mut None => *(&mut self.right) = Some(Box::new(BTree::new(val))),
-                &mut Some(ref mut node) => node.insert(val),
+            expected.insert(val);
+            plain.insert(val);
+            balanced.insert_balanced(val);
+        }
+
+        assert!(balanced.balanced());
+        assert_eq!(plain.len(), expected.len());
+        assert_eq!(balanced.len(), expected.len());
+        check_cached(&plain.root);
+        check_cached(&balanced.root);
+    }
+
+    assert!(balanced.is_height_balanced());
+    assert_eq!(plain.validate(), Ok(()));
+    assert_eq!(balanced.validate(), Ok(()));
+
+    let expected = expected.into_iter().collect::<Vec<_>>();
+
+    let mut actual = Vec::new();
+    in_order(&plain.root, &mut actual);
+    assert_eq!(actual, expected);
+
+    let mut actual = Vec::new();
+    in_order(&balanced.root, &mut actual);
+    assert_eq!(actual, expected);
+
+    // Removing the root of a one-value tree leaves an empty tree we can keep using.
+    let mut t = BTree::new(1);
+    assert!(t.remove(&1));
+    assert_eq!(t.root, None);
+    assert_eq!(t.depth(), 0);
+    assert!(t.balanced());
+    assert!(!t.remove(&1));
+
+    t.insert(2);
+    assert_eq!(t.depth(), 1);
+}
+
+#[test]
+fn test_btree_empty() {
+    let mut t: BTree<u8> = BTree::default();
+
+    assert_eq!(t, BTree::empty());
+    assert!(t.is_empty());
+    assert_eq!(t.len(), 0);
+    assert_eq!(t.depth(), 0);
+    assert!(t.balanced());
+
+    for val in [3, 1, 2, 3, 1] {
+        t.insert(val);
+    }
+
+    assert_eq!(t.len(), 3);
+    assert_eq!(t.depth(), 3);
+
+    for val in [4, 5, 4] {
+        t.insert_balanced(val);
+    }
+
+    assert_eq!(t.len(), 5);
+
+    for val in [1, 2, 3, 4, 5, 6] {
+        t.remove(&val);
+    }
+
+    assert_eq!(t, BTree::empty());
+}
+
+#[test]
+fn test_btree_queries() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-100, 100);
+
+    let mut t = BTree::empty();
+    let mut expected = BTreeSet::new();
+
+    for _ in 0..64 {
+        let val: i32 = rng.sample(distr);
+        t.insert(val);
+        expected.insert(val);
+    }
+
+    assert_eq!(t.min(), expected.iter().next());
+    assert_eq!(t.max(), expected.iter().next_back());
+
+    for val in -110..=110 {
+        assert_eq!(t.contains(&val), expected.contains(&val));
+        assert_eq!(t.floor(&val), expected.range(..=val).next_back());
+        assert_eq!(t.ceiling(&val), expected.range(val..).next());
+        assert_eq!(t.predecessor(&val), expected.range(..val).next_back());
+        assert_eq!(t.successor(&val), expected.range(val + 1..).next());
+    }
+
+    let empty: BTree<i32> = BTree::empty();
+    assert!(!empty.contains(&0));
+    assert_eq!(empty.min(), None);
+    assert_eq!(empty.max(), None);
+    assert_eq!(empty.floor(&0), None);
+    assert_eq!(empty.ceiling(&0), None);
+    assert_eq!(empty.predecessor(&0), None);
+    assert_eq!(empty.successor(&0), None);
+}
+
+#[test]
+fn test_btree_rank_select() {
+    use rand::prelude::*;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(1, 100);
+
+    for balanced in [false, true] {
+        let mut t = BTree::empty();
+
+        let mut sorted = (0..64).map(|_| rng.sample(distr)).collect::<Vec<u8>>();
+
+        for &val in sorted.iter() {
+            if balanced {
+                t.insert_balanced(val);
+            } else {
+                t.insert(val);
+            }
+        }
+
+        sorted.sort();
+        sorted.dedup();
+
+        assert_eq!(t.len(), sorted.len());
+
+        for (k, val) in sorted.iter().enumerate() {
+            assert_eq!(t.select(k), Some(val));
+            assert_eq!(t.rank(val), k);
+        }
+        assert_eq!(t.select(sorted.len()), None);
+
+        for val in 0..=u8::MAX {
+            assert_eq!(t.rank(&val), sorted.iter().filter(|&&other| other < val).count());
+        }
+
+        // Sizes have to survive a full rebuild too.
+        t.balance();
+
+        for (k, val) in sorted.iter().enumerate() {
+            assert_eq!(t.select(k), Some(val));
+            assert_eq!(t.rank(val), k);
+        }
+    }
+}
+
+#[test]
+fn test_btree_deep() {
+    const N: isize = 1_000_000;
+
+    // The degenerate tree sorted `insert`s would build, made by hand, since actually inserting a
+    // million sorted values into a vine takes quadratic time.
+    let mut root = None;
+    for val in (0..N).rev() {
+        let mut node = Node::new(val);
+        node.right = root;
+        node.update();
+        root = Some(Box::new(node));
+    }
+    let mut t = BTree { root, rotations: 0 };
+
+    assert_eq!(t.depth(), N);
+    assert_eq!(t.len(), N as usize);
+
+    // Inserting goes all the way down the vine.
+    t.insert(N);
+    t.insert(N);
+    t.insert(-1);
+
+    assert_eq!(t.depth(), N + 1);
+    assert_eq!(t.len(), N as usize + 2);
+    assert_eq!(t.max(), Some(&N));
+    assert_eq!(t.validate(), Ok(()));
+    assert!(t.iter().copied().eq(-1..=N));
+
+    // Everything else which walks down the tree has to cope with the vine too.
+    let copy = t.clone();
+    assert!(copy == t);
+
+    assert!(t.remove(&(N - 1)));
+    assert!(t.remove(&0));
+    assert!(!t.remove(&0));
+    assert!(t.remove_balanced(&(N / 2)));
+    assert!(copy != t);
+    drop(copy);
+
+    t.extend(N + 1..N + 10);
+    assert_eq!(t.len(), N as usize + 8);
+    assert_eq!(t.validate(), Ok(()));
+
+    let mut upper = t.split_off(&(N / 2));
+    assert_eq!(t.max(), Some(&(N / 2 - 1)));
+    assert_eq!(upper.min(), Some(&(N / 2 + 1)));
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(upper.validate(), Ok(()));
+
+    t.append(&mut upper);
+    assert_eq!(t.len(), N as usize + 8);
+    assert_eq!(t.validate(), Ok(()));
+
+    drop(t);
+
+    // And the same again, but balanced before being dropped.
+    let mut root = None;
+    for val in (0..N).rev() {
+        let mut node = Node::new(val);
+        node.right = root;
+        node.update();
+        root = Some(Box::new(node));
+    }
+    let mut t = BTree { root, rotations: 0 };
+
+    t.balance();
+    assert_eq!(t.depth(), 20);
+    assert!(t.is_height_balanced());
+}
+
+#[test]
+fn test_btree_from_sorted() {
+    use rand::prelude::*;
+
+    let t: BTree<i32> = BTree::from_sorted(&[]);
+    assert!(t.is_empty());
+
+    // Every size up to a few full levels, so we hit every shape of bottom level.
+    for len in 1..=64 {
+        let values = (0..len).collect::<Vec<_>>();
+        let t = BTree::from_sorted(&values);
+
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), values);
+        assert_eq!(t.validate(), Ok(()));
+        assert!(t.is_height_balanced());
+
+        // As shallow as possible: a tree of depth d holds at most 2^d - 1 values.
+        let depth = t.depth() as u32;
+        assert!(len < 1 << depth);
+        assert!(len >= 1 << (depth - 1));
+    }
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-1000, 1000);
+
+    let values = (0..1024).map(|_| rng.sample(distr)).collect::<Vec<i32>>();
+    let t: BTree<i32> = values.iter().copied().collect();
+
+    let mut sorted = values.clone();
+    sorted.sort();
+    sorted.dedup();
+
+    assert_eq!(t.iter().copied().collect::<Vec<_>>(), sorted);
+    assert_eq!(t, BTree::from_sorted(&sorted));
+    assert_eq!(t.validate(), Ok(()));
+
+    let mut t = BTree::empty();
+    t.extend(values.iter().copied());
+    t.extend(0..1000);
+
+    assert_eq!(t.validate(), Ok(()));
+    assert!(t.is_height_balanced());
+    assert_eq!(t.len(), sorted.iter().filter(|val| !(0..1000).contains(*val)).count() + 1000);
+}
+
+#[test]
+#[should_panic(expected = "strictly increasing")]
+fn test_btree_from_sorted_unsorted() {
+    BTree::from_sorted(&[1, 3, 2]);
+}
+
+impl<T: Ord> Default for BTree<T> {
+    fn default() -> Self {
+        BTree::empty()
+    }
+}
+
+///
+/// Dropping a tree takes it apart one node at a time.
+///
+/// Left to itself, Rust drops a `Box<Node>` by dropping its children first, and their children,
+/// and so on, one stack frame per level. That's no problem for a balanced tree, but a tree built
+/// from sorted values with `insert` is as deep as it is long, and would overflow the stack.
+/// Instead we cut each node's children off before it's dropped, and keep them on a stack of our
+/// own.
+///
+impl<T> Drop for BTree<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+///
+/// Cloning copies the tree exactly, shape and all, without recursing.
+///
+/// A derived `Clone` would clone each `Box<Node>` by cloning its children first, one stack frame
+/// per level, just like dropping. Instead we write the tree down pre-order, with a `None` for
+/// every missing child, and then build the copy from that list backwards (the same trick loading
+/// a saved tree uses): going backwards, both of a node's finished sub-trees are on top of the
+/// stack by the time we get to the node.
+///
+/// ```
+/// # use exercises::btree::BTree;
+/// let mut my_tree = BTree::new(2);
+/// my_tree.insert(1);
+///
+/// let mut copy = my_tree.clone();
+/// copy.insert(3);
+///
+/// assert_eq!(my_tree.len(), 2);
+/// assert_eq!(copy.len(), 3);
+/// ```
+///
+impl<T: Clone> Clone for BTree<T> {
+    fn clone(&self) -> BTree<T> {
+        let mut pre_order = Vec::new();
+        let mut stack = vec![self.root.as_deref()];
+        while let Some(slot) = stack.pop() {
+            if let Some(node) = slot {
+                stack.push(node.right.as_deref());
+                stack.push(node.left.as_deref());
             }
+            pre_order.push(slot);
+        }
+
+        let mut built: Vec<Option<Box<Node<T>>>> = Vec::new();
+        for slot in pre_order.into_iter().rev() {
+            let copy = slot.map(|node| {
+                let left = built.pop().expect("pre-order has both children before a node");
+                let right = built.pop().expect("pre-order has both children before a node");
+                Box::new(Node {
+                    value: node.value.clone(),
+                    height: node.height,
+                    size: node.size,
+                    left,
+                    right,
+                })
+            });
+            built.push(copy);
+        }
+
+        BTree {
+            root: built.pop().expect("pre-order always has the root"),
+            rotations: self.rotations,
         }
     }
+}
 
-    /// Determines if a tree is balanced using the depth method.
-    ///
-    /// The tree is unbalanced if both branches differ in depth by more than 1
-    ///
-    /// Note that we could implement this without the use of `depth` by making recursive calls to
-    /// `balanced` and have some logic that checks not only the immediate `left` and `right` nodes,
-    /// but the child nodes as well.
+///
+/// Two trees are equal if they have the same shape with the same values in the same places.
+///
+/// That's stricter than holding the same values: a vine and a balanced tree of `1..=3` are not
+/// equal, compare `iter()`s for that. Like `Clone`, this walks both trees side by side with a
+/// stack of our own instead of recursing.
+///
+/// ```
+/// # use exercises::btree::BTree;
+/// let mut vine = BTree::new(1);
+/// vine.insert(2);
+/// vine.insert(3);
+///
+/// let balanced: BTree<i32> = (1..=3).collect();
+///
+/// assert_ne!(vine, balanced);
+/// assert_eq!(vine.iter().eq(balanced.iter()), true);
+/// ```
+///
+impl<T: PartialEq> PartialEq for BTree<T> {
+    fn eq(&self, other: &BTree<T>) -> bool {
+        let mut stack = vec![(self.root.as_deref(), other.root.as_deref())];
+        while let Some(pair) = stack.pop() {
+            match pair {
+                (None, None) => (),
+                (Some(a), Some(b)) => {
+                    if a.value != b.value {
+                        return false;
+                    }
+                    stack.push((a.left.as_deref(), b.left.as_deref()));
+                    stack.push((a.right.as_deref(), b.right.as_deref()));
+                },
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+///
+/// Collecting into a tree sorts the values first and then builds a balanced tree from them in one
+/// go, rather than inserting them one at a time.
+///
+/// Duplicates are dropped, the first one wins, the same as if they had been inserted in order.
+///
+/// ```
+/// # use exercises::btree::BTree;
+/// let my_tree: BTree<i32> = (1..=7).collect();
+///
+/// assert_eq!(my_tree.len(), 7);
+/// assert_eq!(my_tree.depth(), 3);
+/// ```
+///
+impl<T: Ord> FromIterator<T> for BTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> BTree<T> {
+        let mut values: Vec<T> = iter.into_iter().collect();
+        values.sort();
+        values.dedup();
+
+        BTree {
+            root: build_balanced(values.len(), &mut values.into_iter()),
+            rotations: 0,
+        }
+    }
+}
+
+///
+/// Extending a tree uses `insert_balanced`, so sorted input doesn't turn the tree into a vine.
+///
+/// ```
+/// # use exercises::btree::BTree;
+/// let mut my_tree = BTree::new(0);
+/// my_tree.extend(1..100);
+///
+/// assert_eq!(my_tree.len(), 100);
+/// assert_eq!(my_tree.is_height_balanced(), true);
+/// ```
+///
+impl<T: Ord> Extend<T> for BTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert_balanced(val);
+        }
+    }
+}
+
+impl<T: Ord> BTree<T> {
+    pub fn new(val: T) -> BTree<T> {
+        BTree {
+            root: Some(Box::new(Node::new(val))),
+            rotations: 0,
+        }
+    }
+
+    /// Create a tree with nothing in it.
     ///
-    /// This solution is more concise and reuses logic we would have anyway in a tree type.
+    /// Handy when you don't have a value to start the tree with yet.
     ///
     /// ```
     /// # use exercises::btree::BTree;
-    /// let mut my_tree = BTree::new(10);
+    /// let mut my_tree = BTree::empty();
     ///
+    /// assert_eq!(my_tree.is_empty(), true);
+    /// assert_eq!(my_tree.depth(), 0);
     /// assert_eq!(my_tree.balanced(), true);
     ///
-    /// my_tree.insert(11);
-    /// my_tree.insert(12);
+    /// my_tree.insert(5);
     ///
-    /// assert_eq!(my_tree.balanced(), false);
+    /// assert_eq!(my_tree.len(), 1);
+    /// assert_eq!(my_tree.depth(), 1);
+    /// ```
+    pub fn empty() -> BTree<T> {
+        BTree {
+            root: None,
+            rotations: 0,
+        }
+    }
+
+    /// Build a perfectly balanced tree from values which are already sorted.
+    ///
+    /// The middle value becomes the root, the values before it make up the left sub-tree and the
+    /// values after it the right, and so on down. We hand out the values in order as we go, so
+    /// every value is cloned once and the whole thing is O(n), no comparisons or rotations needed.
+    ///
+    /// Every level is full apart from the bottom one, which is as shallow as a tree can get.
+    ///
+    /// Panics if the values are not strictly increasing, since that would make a tree which
+    /// isn't sorted (or has duplicates in it).
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let my_tree = BTree::from_sorted(&[1, 2, 3, 4, 5, 6, 7]);
+    ///
+    /// assert_eq!(my_tree.to_string(), "4 -> 6 -> 7\n|     `-> 5\n`-> 2 -> 3\n     `-> 1");
+    /// assert_eq!(my_tree.depth(), 3);
+    /// ```
+    pub fn from_sorted(values: &[T]) -> BTree<T>
+    where
+        T: Clone,
+    {
+        assert!(
+            values.windows(2).all(|pair| pair[0] < pair[1]),
+            "from_sorted needs strictly increasing values"
+        );
+
+        BTree {
+            root: build_balanced(values.len(), &mut values.iter().cloned()),
+            rotations: 0,
+        }
+    }
+
+    /// Number of values in the tree.
+    ///
+    /// Duplicates are not stored, so they are not counted.
+    ///
+    /// Every node keeps track of how many values are in its sub-tree, so this is just the size of
+    /// the root.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(5);
+    /// my_tree.insert(3);
+    /// my_tree.insert(3);
+    ///
+    /// assert_eq!(my_tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Insert a value into the tree
+    ///
+    /// Duplicate values are ignored, use a [TreeMultiset] to keep count of them instead.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(5);
+    /// my_tree.insert(3);
+    /// my_tree.insert(7);
+    /// ```
+    pub fn insert(&mut self, val: T) {
+        match self.root {
+            None => self.root = Some(Box::new(Node::new(val))),
+            Some(ref mut node) => {
+                node.insert(val);
+            },
+        }
+    }
+
+    /// Insert a value into the tree, keeping the tree balanced.
+    ///
+    /// This is a regular `insert` followed by an AVL style rebalance of every node on the way back
+    /// up to the root.
+    /// Because only the nodes on the path we inserted along can change height, those are the only
+    /// nodes which can become unbalanced, so those are the only nodes we need to check.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(1);
+    /// my_tree.insert_balanced(2);
+    /// my_tree.insert_balanced(3);
+    ///
+    /// assert_eq!(my_tree.balanced(), true);
+    /// assert_eq!(my_tree.depth(), 2);
+    /// ```
+    pub fn insert_balanced(&mut self, val: T) {
+        insert_balanced(&mut self.root, val, &mut self.rotations);
+    }
+
+    /// Remove a value from the tree, returning whether it was there to remove.
+    ///
+    /// There are three cases depending on how many children the node holding `val` has:
+    /// * No children: we just cut the node off of its parent.
+    /// * One child: the child takes the node's place.
+    /// * Two children: the smallest value bigger than `val` (the in-order successor, the left-most
+    ///   node of the right sub-tree) is unlinked from where it was and takes the node's place.
+    ///
+    /// Like `insert` this does not do any rebalancing, see `remove_balanced` for that.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(5);
+    /// my_tree.insert(3);
+    /// my_tree.insert(7);
+    ///
+    /// assert_eq!(my_tree.remove(&5), true);
+    /// assert_eq!(my_tree.remove(&5), false);
+    ///
+    /// assert_eq!(my_tree.remove(&3), true);
+    /// assert_eq!(my_tree.remove(&7), true);
+    ///
+    /// // The tree is empty now, which is balanced and has no depth.
+    /// assert_eq!(my_tree.depth(), 0);
+    /// assert_eq!(my_tree.balanced(), true);
+    /// ```
+    pub fn remove(&mut self, val: &T) -> bool {
+        self.remove_by(&|other: &T| val.cmp(other), false).is_some()
+    }
+
+    /// Remove a value from the tree, keeping the tree balanced.
+    ///
+    /// The same as `remove`, but every node on the path back up to the root gets the same AVL
+    /// rebalance `insert_balanced` does.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(1);
+    /// for val in 2..=7 {
+    ///     my_tree.insert_balanced(val);
+    /// }
+    ///
+    /// my_tree.remove_balanced(&1);
+    /// my_tree.remove_balanced(&3);
+    /// my_tree.remove_balanced(&2);
+    ///
+    /// assert_eq!(my_tree.balanced(), true);
+    /// assert_eq!(my_tree.depth(), 3);
+    /// ```
+    pub fn remove_balanced(&mut self, val: &T) -> bool {
+        self.remove_by(&|other: &T| val.cmp(other), true).is_some()
+    }
+
+    /// Re-arrange the tree in place so it is as shallow as possible.
+    ///
+    /// This is the Day-Stout-Warren algorithm:
+    /// 1. Rotate right until the whole tree is one long right-leaning "vine" (a sorted linked list).
+    /// 2. Rotate every other node on the vine left, which halves the vine's length and hangs the
+    ///    nodes we skipped off the left of the nodes we rotated.
+    ///    Repeat until the vine is one node long.
+    ///
+    /// The first pass over the vine only rotates the "extra" nodes which don't fit in a perfect
+    /// tree, those end up as the leaves on the bottom level.
+    ///
+    /// Every step is a rotation, so we never allocate or clone a node, and each node is rotated a
+    /// constant number of times on average, making the whole thing O(n).
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(1);
+    /// for val in 2..=7 {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.depth(), 7);
+    /// assert_eq!(my_tree.balanced(), false);
+    ///
+    /// my_tree.balance();
+    ///
+    /// assert_eq!(my_tree.depth(), 3);
+    /// assert_eq!(my_tree.balanced(), true);
+    /// ```
+    pub fn balance(&mut self) {
+        if let Some(ref mut node) = self.root {
+            self.rotations += node.balance();
+        }
+    }
+
+    /// Determines if a tree is balanced using the depth method.
+    ///
+    /// The tree is unbalanced if both branches differ in depth by more than 1
+    ///
+    /// Note that we could implement this without the use of `depth` by making recursive calls to
+    /// `balanced` and have some logic that checks not only the immediate `left` and `right` nodes,
+    /// but the child nodes as well.
+    ///
+    /// This solution is more concise and reuses logic we would have anyway in a tree type.
+    ///
+    /// Note this only looks at the root, a tree can be lopsided further down and still be
+    /// "balanced" here. See `is_height_balanced` for the thorough check.
+    ///
+    /// An empty tree is balanced.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(10);
+    ///
+    /// assert_eq!(my_tree.balanced(), true);
+    ///
+    /// my_tree.insert(11);
+    /// my_tree.insert(12);
+    ///
+    /// assert_eq!(my_tree.balanced(), false);
     ///
     /// my_tree.insert(1);
     /// my_tree.insert(2);
@@ -153,27 +995,16 @@ impl<'a> BTree<'a> {
     /// assert_eq!(my_tree.balanced(), true);
     /// ```
     pub fn balanced(&self) -> bool {
-        let left = match &self.left {
-            Some(node) => node.depth(),
-            None => 0,
-        };
-        let right = match &self.right {
-            Some(node) => node.depth(),
-            None => 0,
-        };
-        [left-1, left, left+1].contains(&right) && [right-1, right, right+1].contains(&left)
+        self.root.as_ref().is_none_or(|node| node.balanced())
     }
 
     /// Determines the depth of a tree.
     ///
-    /// Returns max depth of all on this tree.
-    ///
-    /// Does this dynamically, making it an expensive operation.
-    /// The tradeoff is we do less book-keeping when inserting and balancing the tree keeping track
-    /// of depths at that point.
+    /// Returns max depth of all on this tree, or 0 for an empty tree.
     ///
-    /// If this were used in prod we would most certainly add a "depth" field to the "BTree"
-    /// struct that cached the depth of each tree node.
+    /// This used to be calculated dynamically, making it an expensive operation.
+    /// Now every node caches its own height, which `insert` and the rotations keep up to date, so
+    /// this is a constant time lookup.
     ///
     /// ```
     /// # use exercises::btree::BTree;
@@ -194,14 +1025,839 @@ impl<'a> BTree<'a> {
     /// assert_eq!(my_tree.depth(), 4);
     /// ```
     pub fn depth(&self) -> isize {
-        let right = match &self.right {
-            Some(node) => node.depth(),
+        self.root.as_ref().map_or(0, |node| node.height)
+    }
+
+    /// How many rotations balancing has done over the life of the tree.
+    ///
+    /// That's every rotation `insert_balanced`, `remove_balanced`, `balance` and joining trees
+    /// back together do. Plain `insert` and `remove` never rotate. The count is kept the same way
+    /// [RedBlackTree::rotations] is, so the two can be compared on the same values.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(1);
+    /// my_tree.insert_balanced(2);
+    ///
+    /// assert_eq!(my_tree.rotations(), 0);
+    ///
+    /// // Three in a row leans too far right, so it needs a rotation.
+    /// my_tree.insert_balanced(3);
+    ///
+    /// assert_eq!(my_tree.rotations(), 1);
+    /// ```
+    pub fn rotations(&self) -> usize {
+        self.rotations
+    }
+
+    /// Is `val` in the tree?
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(5);
+    /// my_tree.insert(3);
+    ///
+    /// assert_eq!(my_tree.contains(&3), true);
+    /// assert_eq!(my_tree.contains(&4), false);
+    /// ```
+    pub fn contains(&self, val: &T) -> bool {
+        self.find_by(|other| val.cmp(other)).is_some()
+    }
+
+    /// The smallest value in the tree, found by going left until we can't.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(5);
+    /// my_tree.insert(3);
+    /// my_tree.insert(8);
+    ///
+    /// assert_eq!(my_tree.min(), Some(&3));
+    /// assert_eq!(my_tree.max(), Some(&8));
+    /// ```
+    pub fn min(&self) -> Option<&T> {
+        let mut node = self.root.as_deref()?;
+        while let Some(ref left) = node.left {
+            node = left;
+        }
+        Some(&node.value)
+    }
+
+    /// The largest value in the tree, found by going right until we can't.
+    pub fn max(&self) -> Option<&T> {
+        let mut node = self.root.as_deref()?;
+        while let Some(ref right) = node.right {
+            node = right;
+        }
+        Some(&node.value)
+    }
+
+    /// The largest value in the tree which is less than or equal to `val`.
+    ///
+    /// We search for `val` as usual, and every time we go right we've passed a value which is
+    /// smaller than `val`, so we remember it as the best answer so far.
+    /// If we find `val` itself, that is the answer.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(10);
+    /// my_tree.insert(5);
+    /// my_tree.insert(15);
+    ///
+    /// assert_eq!(my_tree.floor(&12), Some(&10));
+    /// assert_eq!(my_tree.floor(&15), Some(&15));
+    /// assert_eq!(my_tree.floor(&4), None);
+    /// ```
+    pub fn floor(&self, val: &T) -> Option<&T> {
+        self.closest(|other| val.cmp(other), true)
+    }
+
+    /// The smallest value in the tree which is greater than or equal to `val`.
+    ///
+    /// The mirror image of `floor`.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(10);
+    /// my_tree.insert(5);
+    /// my_tree.insert(15);
+    ///
+    /// assert_eq!(my_tree.ceiling(&12), Some(&15));
+    /// assert_eq!(my_tree.ceiling(&5), Some(&5));
+    /// assert_eq!(my_tree.ceiling(&16), None);
+    /// ```
+    pub fn ceiling(&self, val: &T) -> Option<&T> {
+        self.closest(|other| val.cmp(other), false)
+    }
+
+    /// The largest value in the tree which is strictly less than `val`.
+    ///
+    /// `val` does not have to be in the tree.
+    ///
+    /// This is a `floor` where we pretend `val` is a hair smaller than it is: we never stop when we
+    /// find `val`, we keep looking to its left.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(10);
+    /// my_tree.insert(5);
+    /// my_tree.insert(15);
+    ///
+    /// assert_eq!(my_tree.predecessor(&10), Some(&5));
+    /// assert_eq!(my_tree.predecessor(&11), Some(&10));
+    /// assert_eq!(my_tree.predecessor(&5), None);
+    /// ```
+    pub fn predecessor(&self, val: &T) -> Option<&T> {
+        self.closest(|other| val.cmp(other).then(Ordering::Less), true)
+    }
+
+    /// The smallest value in the tree which is strictly greater than `val`.
+    ///
+    /// The mirror image of `predecessor`.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(10);
+    /// my_tree.insert(5);
+    /// my_tree.insert(15);
+    ///
+    /// assert_eq!(my_tree.successor(&10), Some(&15));
+    /// assert_eq!(my_tree.successor(&9), Some(&10));
+    /// assert_eq!(my_tree.successor(&15), None);
+    /// ```
+    pub fn successor(&self, val: &T) -> Option<&T> {
+        self.closest(|other| val.cmp(other).then(Ordering::Greater), false)
+    }
+
+    /// The number of values in the tree which are less than `val`.
+    ///
+    /// `val` does not have to be in the tree.
+    ///
+    /// We walk down as if searching for `val`, and every time we go right we've skipped past the
+    /// current node and its entire left sub-tree, all of which are smaller than `val`.
+    /// Since every node knows its size, that's O(height) instead of counting one by one.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::empty();
+    /// for val in [50, 20, 80, 10, 30] {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.rank(&10), 0);
+    /// assert_eq!(my_tree.rank(&30), 2);
+    /// assert_eq!(my_tree.rank(&31), 3);
+    /// assert_eq!(my_tree.rank(&100), 5);
+    /// ```
+    pub fn rank(&self, val: &T) -> usize {
+        let mut rank = 0;
+        let mut node = self.root.as_deref();
+
+        while let Some(n) = node {
+            if n.value < *val {
+                rank += 1 + size(&n.left);
+                node = n.right.as_deref();
+            } else {
+                node = n.left.as_deref();
+            }
+        }
+
+        rank
+    }
+
+    /// The `k`-th smallest value in the tree, counting from 0.
+    ///
+    /// The opposite of `rank`: `select(rank(&val)) == Some(&val)` for any `val` in the tree.
+    ///
+    /// At each node the left sub-tree holds the `size(left)` smallest values, so `k` is either in
+    /// there, is this node, or is `k - size(left) - 1` values into the right sub-tree.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::empty();
+    /// for val in [50, 20, 80, 10, 30] {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.select(0), Some(&10));
+    /// assert_eq!(my_tree.select(2), Some(&30));
+    /// assert_eq!(my_tree.select(4), Some(&80));
+    /// assert_eq!(my_tree.select(5), None);
+    /// ```
+    pub fn select(&self, k: usize) -> Option<&T> {
+        let mut k = k;
+        let mut node = self.root.as_deref();
+
+        while let Some(n) = node {
+            let left = size(&n.left);
+            match k.cmp(&left) {
+                Ordering::Less => node = n.left.as_deref(),
+                Ordering::Equal => return Some(&n.value),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    node = n.right.as_deref();
+                },
+            }
+        }
+
+        None
+    }
+
+    ///
+    /// Private API: closest
+    ///
+    /// The shared walk behind `floor`, `ceiling`, `predecessor` and `successor`.
+    ///
+    /// Searches like `find_by`, returning an exact match if there is one. Otherwise returns the
+    /// closest value below (`below == true`) or above the target we passed on the way down.
+    ///
+    /// `predecessor` and `successor` never report `Equal`, so they never stop on an exact match.
+    ///
+    fn closest<F>(&self, cmp: F, below: bool) -> Option<&T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let mut best = None;
+        let mut node = self.root.as_deref();
+
+        while let Some(n) = node {
+            match cmp(&n.value) {
+                Ordering::Equal => return Some(&n.value),
+                Ordering::Less => {
+                    if !below {
+                        best = Some(&n.value);
+                    }
+                    node = n.left.as_deref();
+                },
+                Ordering::Greater => {
+                    if below {
+                        best = Some(&n.value);
+                    }
+                    node = n.right.as_deref();
+                },
+            }
+        }
+
+        best
+    }
+
+    ///
+    /// Private API: find_by
+    ///
+    /// Walks down the tree looking for a value, steering with `cmp`.
+    ///
+    /// `cmp` says how the value we're looking for compares to the value of the node we're on, so
+    /// callers can search by something other than a whole `T`, like the key of a key/value pair.
+    ///
+    fn find_by<F>(&self, cmp: F) -> Option<&T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let mut node = self.root.as_ref()?;
+        loop {
+            match cmp(&node.value) {
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Less => node = node.left.as_ref()?,
+                Ordering::Greater => node = node.right.as_ref()?,
+            }
+        }
+    }
+
+    ///
+    /// Private API: find_by_mut
+    ///
+    /// Same as `find_by` but hands back a mutable reference.
+    ///
+    /// Be careful, changing the value in a way that changes its ordering breaks the tree.
+    ///
+    fn find_by_mut<F>(&mut self, cmp: F) -> Option<&mut T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let mut node = self.root.as_mut()?;
+        loop {
+            match cmp(&node.value) {
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Less => node = node.left.as_mut()?,
+                Ordering::Greater => node = node.right.as_mut()?,
+            }
+        }
+    }
+
+    ///
+    /// Private API: remove_by
+    ///
+    /// Removes the value `cmp` steers us to, optionally rebalancing on the way back up.
+    ///
+    fn remove_by<F>(&mut self, cmp: &F, rebalance: bool) -> Option<T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        remove_by(&mut self.root, cmp, rebalance, &mut self.rotations)
+    }
+}
+
+impl<T: Ord> Node<T> {
+    fn new(val: T) -> Node<T> {
+        Node {
+            value: val,
+            height: 1,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    ///
+    /// Private API: insert
+    ///
+    /// Returns whether the value was actually added, that is, it was not a duplicate.
+    ///
+    /// This used to recurse down to where the new node goes and fix up heights on the way back
+    /// up, which means one stack frame per level. Sorted input builds a tree as deep as it is
+    /// long, so a big enough one would overflow the stack.
+    ///
+    /// Now it's two loops. The first walks down to find out whether `val` is a duplicate and, if
+    /// not, how deep the new node will be. Knowing that, the second walk down can fix up each node
+    /// before we pass it: one more value in its sub-tree, and a height of at least the distance
+    /// down to the new node.
+    ///
+    fn insert(&mut self, val: T) -> bool {
+        // Credit to this article for helping me break through lifetimes confusion
+        // https://gist.github.com/aidanhs/5ac9088ca0f6bdd4a370
+        let mut depth = 0;
+        let mut node: &Node<T> = self;
+        loop {
+            // Duplicate values are not stored
+            let next = match val.cmp(&node.value) {
+                Ordering::Equal => return false,
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+            };
+            match next {
+                // We match on Some(ref ...) so we don't move out of the node,
+                // just use a refernece to it.
+                Some(ref child) => node = child,
+                None => break,
+            }
+            depth += 1;
+        }
+
+        // The new node will be `depth + 1` levels below us.
+        let mut node: &mut Node<T> = self;
+        loop {
+            node.size += 1;
+            node.height = std::cmp::max(node.height, depth + 2);
+            depth -= 1;
+
+            let next = if val < node.value { &mut node.left } else { &mut node.right };
+            match next {
+                Some(child) => node = child,
+                None => {
+                    *next = Some(Box::new(Node::new(val)));
+                    return true;
+                },
+            }
+        }
+    }
+
+    ///
+    /// Private API: balance
+    ///
+    /// Day-Stout-Warren, see `BTree::balance`. Returns how many rotations it took.
+    ///
+    fn balance(&mut self) -> usize {
+        let mut rotations = 0;
+
+        // Step 1: tree to vine.
+        // Any time the current node has a left child we rotate it up, once there are no more left
+        // children we move down the vine.
+        let mut size = 0;
+        let mut node: &mut Node<T> = self;
+        loop {
+            while node.left.is_some() {
+                node.rotate_right();
+                rotations += 1;
+            }
+            size += 1;
+            match node.right {
+                Some(ref mut next) => node = next,
+                None => break,
+            }
+        }
+
+        // Step 2: vine to tree.
+        // A perfect tree has 2^k - 1 nodes, anything past that goes on the bottom level.
+        let mut perfect = 1;
+        while perfect * 2 < size {
+            perfect = perfect * 2 + 1;
+        }
+        rotations += self.compress(size - perfect);
+
+        let mut remaining = perfect;
+        while remaining > 1 {
+            remaining /= 2;
+            rotations += self.compress(remaining);
+        }
+
+        // The rotations only fix up heights locally, so we recalculate them all once at the end.
+        self.update_all();
+
+        rotations
+    }
+
+    ///
+    /// Private API: compress
+    ///
+    /// Rotates left `count` times down the right spine of the tree, skipping one node after each
+    /// rotation. Returns how many rotations that was.
+    ///
+    fn compress(&mut self, count: usize) -> usize {
+        let mut node: &mut Node<T> = self;
+        for done in 1..=count {
+            node.rotate_left();
+            match node.right {
+                Some(ref mut next) => node = next,
+                None => return done,
+            }
+        }
+        count
+    }
+
+    ///
+    /// Private API: update_all
+    ///
+    /// Recalculates the cached height and size of every node in the tree, bottom up.
+    ///
+    fn update_all(&mut self) {
+        if let Some(ref mut left) = self.left {
+            left.update_all();
+        }
+        if let Some(ref mut right) = self.right {
+            right.update_all();
+        }
+        self.update();
+    }
+
+    fn balanced(&self) -> bool {
+        let left = match &self.left {
+            Some(node) => node.height,
             None => 0,
         };
-        let left = match &self.left {
-            Some(node) => node.depth(),
+        let right = match &self.right {
+            Some(node) => node.height,
             None => 0,
         };
-        1 + std::cmp::max(left, right)
+        [left-1, left, left+1].contains(&right) && [right-1, right, right+1].contains(&left)
+    }
+
+    ///
+    /// Private API: update
+    ///
+    /// Re-calculates this node's cached height and size from the cached values of its children.
+    ///
+    /// Must be called any time a child is added, removed, or re-arranged.
+    ///
+    fn update(&mut self) {
+        let left = self.left.as_ref().map_or(0, |node| node.height);
+        let right = self.right.as_ref().map_or(0, |node| node.height);
+        self.height = 1 + std::cmp::max(left, right);
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    ///
+    /// Private API: balance_factor
+    ///
+    /// How much taller the left side of this node is than the right.
+    /// Anything outside of -1..=1 means this node is unbalanced.
+    ///
+    fn balance_factor(&self) -> isize {
+        let left = self.left.as_ref().map_or(0, |node| node.height);
+        let right = self.right.as_ref().map_or(0, |node| node.height);
+        left - right
+    }
+
+    ///
+    /// Private API: rotate_right
+    ///
+    /// ```text
+    ///       5            3
+    ///      / \          / \
+    ///     3   6   =>   2   5
+    ///    / \              / \
+    ///   2   4            4   6
+    /// ```
+    ///
+    /// The tricky bit here is that `self` is the root of the rotation, and we can't move `self`.
+    /// Instead we take the left child out, swap its contents with our own, and then hang what used
+    /// to be `self` off the right of the new root.
+    ///
+    fn rotate_right(&mut self) {
+        let mut pivot = match self.left.take() {
+            Some(node) => node,
+            None => return,
+        };
+
+        self.left = pivot.right.take();
+        self.update();
+
+        // `self` is now the old left child, `pivot` is the old root.
+        std::mem::swap(self, &mut *pivot);
+
+        self.right = Some(pivot);
+        self.update();
+    }
+
+    ///
+    /// Private API: rotate_left
+    ///
+    /// The mirror image of `rotate_right`.
+    ///
+    fn rotate_left(&mut self) {
+        let mut pivot = match self.right.take() {
+            Some(node) => node,
+            None => return,
+        };
+
+        self.right = pivot.left.take();
+        self.update();
+
+        std::mem::swap(self, &mut *pivot);
+
+        self.left = Some(pivot);
+        self.update();
+    }
+
+    ///
+    /// Private API: rebalance
+    ///
+    /// Restores the AVL property at this node, assuming both children are already AVL balanced and
+    /// differ in height by at most 2.
+    ///
+    /// There are four cases, named after the path to the node which is too deep:
+    /// * left-left: rotate right.
+    /// * left-right: rotate the left child left, then rotate right.
+    /// * right-right: rotate left.
+    /// * right-left: rotate the right child right, then rotate left.
+    ///
+    /// Returns how many rotations that took, 0, 1 or 2.
+    ///
+    fn rebalance(&mut self) -> usize {
+        self.update();
+
+        let factor = self.balance_factor();
+        let mut rotations = 0;
+
+        if factor > 1 {
+            if let Some(ref mut left) = self.left {
+                if left.balance_factor() < 0 {
+                    left.rotate_left();
+                    rotations += 1;
+                }
+            }
+            self.rotate_right();
+            rotations += 1;
+        } else if factor < -1 {
+            if let Some(ref mut right) = self.right {
+                if right.balance_factor() > 0 {
+                    right.rotate_right();
+                    rotations += 1;
+                }
+            }
+            self.rotate_left();
+            rotations += 1;
+        }
+
+        rotations
+    }
+
+    ///
+    /// Private API: repair
+    ///
+    /// Fixes this node up after one of its sub-trees has changed, either by rebalancing or by just
+    /// updating the cached height. Returns how many rotations rebalancing took.
+    ///
+    fn repair(&mut self, rebalance: bool) -> usize {
+        if rebalance {
+            self.rebalance()
+        } else {
+            self.update();
+            0
+        }
+    }
+
+}
+
+///
+/// Private API: size
+///
+/// Number of values in the (sub-)tree hanging off of `slot`.
+///
+fn size<T>(slot: &Option<Box<Node<T>>>) -> usize {
+    slot.as_ref().map_or(0, |node| node.size)
+}
+
+///
+/// Private API: build_balanced
+///
+/// Builds a balanced (sub-)tree out of the next `len` values of `values`, which must be sorted.
+///
+/// The left sub-tree has to be built first so that it takes the smallest values, then the middle
+/// value is ours, and whatever is left over goes to the right.
+/// Heights and sizes are filled in bottom up as each node is finished.
+///
+/// This recurses, but only as deep as the tree it builds, which is O(log n).
+///
+fn build_balanced<T, I>(len: usize, values: &mut I) -> Option<Box<Node<T>>>
+where
+    T: Ord,
+    I: Iterator<Item = T>,
+{
+    if len == 0 {
+        return None;
+    }
+
+    let left = build_balanced(len / 2, values);
+    let mut node = Node::new(values.next().expect("build_balanced ran out of values"));
+    node.left = left;
+    node.right = build_balanced(len - len / 2 - 1, values);
+    node.update();
+
+    Some(Box::new(node))
+}
+
+///
+/// Private API: Path
+///
+/// The nodes we've cut loose on the way down a tree, top first, along with which way we went
+/// from each one.
+///
+/// Nodes don't know their parents, so walking down with a `&mut Node` leaves no way back up
+/// short of recursing. Cutting each child off of its parent as we pass means we own the whole
+/// path instead, and `zip_up` can put it back together bottom up with a plain loop. While a node
+/// is on the path its cached height and size are stale, `zip_up` fixes them up.
+///
+type Path<T> = Vec<(Box<Node<T>>, Direction)>;
+
+///
+/// Private API: step_down
+///
+/// Cuts the child in `direction` off of `node` and returns it, putting `node` on the path.
+///
+fn step_down<T>(
+    path: &mut Path<T>,
+    mut node: Box<Node<T>>,
+    direction: Direction,
+) -> Option<Box<Node<T>>> {
+    let child = match direction {
+        Direction::Left => node.left.take(),
+        Direction::Right => node.right.take(),
+    };
+    path.push((node, direction));
+    child
+}
+
+///
+/// Private API: zip_up
+///
+/// Hangs `slot` back where it was cut off of the bottom of `path`, then re-attaches the rest of
+/// the path bottom up, repairing each node as it gets its child back. Returns the new top.
+///
+/// Like everything else here which might rebalance, it adds the rotations it does to the tree's
+/// running count in `rotations`.
+///
+fn zip_up<T: Ord>(
+    path: &mut Path<T>,
+    mut slot: Option<Box<Node<T>>>,
+    rebalance: bool,
+    rotations: &mut usize,
+) -> Option<Box<Node<T>>> {
+    while let Some((mut node, direction)) = path.pop() {
+        match direction {
+            Direction::Left => node.left = slot,
+            Direction::Right => node.right = slot,
+        }
+        *rotations += node.repair(rebalance);
+        slot = Some(node);
+    }
+    slot
+}
+
+///
+/// Private API: insert_balanced
+///
+/// Inserts `val` into the (sub-)tree hanging off of `slot` and rebalances every node on the way
+/// back up. Returns whether the value was actually added.
+///
+/// Iterative, with the same cut-and-zip trick as `remove_by`, so extending a vine someone built
+/// with `insert` doesn't overflow the stack.
+///
+fn insert_balanced<T>(slot: &mut Option<Box<Node<T>>>, val: T, rotations: &mut usize) -> bool
+where
+    T: Ord,
+{
+    let mut path = Vec::new();
+    let mut current = slot.take();
+
+    let inserted = loop {
+        let node = match current.take() {
+            Some(node) => node,
+            None => {
+                current = Some(Box::new(Node::new(val)));
+                break true;
+            },
+        };
+        current = match val.cmp(&node.value) {
+            Ordering::Less => step_down(&mut path, node, Direction::Left),
+            Ordering::Greater => step_down(&mut path, node, Direction::Right),
+            Ordering::Equal => {
+                current = Some(node);
+                break false;
+            },
+        };
+    };
+
+    *slot = zip_up(&mut path, current, inserted, rotations);
+    inserted
+}
+
+///
+/// Private API: remove_by
+///
+/// Removes the value `cmp` steers us to from the (sub-)tree hanging off of `slot`, fixing up
+/// heights and sizes (or rebalancing, if asked to) on the way back up.
+///
+/// This works on the `Option<Box<...>>` slot rather than a node because removing a node means
+/// replacing it in its parent, and a node can't replace itself.
+///
+/// This used to recurse, one stack frame per level like `insert` did. Now we cut the path loose
+/// on the way down and zip it back up afterwards, see [Path].
+///
+fn remove_by<T, F>(
+    slot: &mut Option<Box<Node<T>>>,
+    cmp: &F,
+    rebalance: bool,
+    rotations: &mut usize,
+) -> Option<T>
+where
+    T: Ord,
+    F: Fn(&T) -> Ordering,
+{
+    let mut path = Vec::new();
+    let mut current = slot.take();
+
+    let removed = loop {
+        let node = match current.take() {
+            Some(node) => node,
+            None => break None,
+        };
+        current = match cmp(&node.value) {
+            Ordering::Less => step_down(&mut path, node, Direction::Left),
+            Ordering::Greater => step_down(&mut path, node, Direction::Right),
+            Ordering::Equal => {
+                current = Some(node);
+                break take_node(&mut current, rebalance, rotations);
+            },
+        };
+    };
+
+    // If nothing was removed nothing changed, so there's nothing to rebalance either.
+    *slot = zip_up(&mut path, current, rebalance && removed.is_some(), rotations);
+    removed
+}
+
+///
+/// Private API: take_node
+///
+/// Unlinks the node in `slot` and returns its value.
+///
+/// There are three cases:
+/// * No children: the slot becomes empty.
+/// * One child: the child takes the node's place.
+/// * Two children: the smallest node of the right sub-tree (the in-order successor) takes the
+///   node's place.
+///
+fn take_node<T: Ord>(
+    slot: &mut Option<Box<Node<T>>>,
+    rebalance: bool,
+    rotations: &mut usize,
+) -> Option<T> {
+    let mut node = slot.take()?;
+
+    *slot = match (node.left.take(), node.right.take()) {
+        (None, None) => None,
+        (Some(child), None) | (None, Some(child)) => Some(child),
+        (Some(left), Some(right)) => {
+            let mut right = Some(right);
+            let mut successor = take_min(&mut right, rebalance, rotations);
+            successor.left = Some(left);
+            successor.right = right;
+            *rotations += successor.repair(rebalance);
+            Some(successor)
+        },
+    };
+
+    Some(node.value)
+}
+
+///
+/// Private API: take_min
+///
+/// Unlinks the left-most node of the (non-empty) sub-tree in `slot`.
+///
+fn take_min<T: Ord>(
+    slot: &mut Option<Box<Node<T>>>,
+    rebalance: bool,
+    rotations: &mut usize,
+) -> Box<Node<T>> {
+    let mut path = Vec::new();
+    let mut min = slot.take().expect("take_min on an empty slot");
+
+    while min.left.is_some() {
+        min = step_down(&mut path, min, Direction::Left).expect("checked for a left child");
     }
+
+    *slot = zip_up(&mut path, min.right.take(), rebalance, rotations);
+    min.update();
+    min
 }
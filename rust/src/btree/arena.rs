@@ -0,0 +1,998 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::ops::RangeBounds;
+
+use super::iter::{after_start, before_end};
+
+///
+/// ArenaTree
+///
+/// The same tree as [BTree](super::BTree), laid out differently in memory.
+///
+/// `BTree` gives every node its own `Box`, so the nodes end up wherever the allocator felt like
+/// putting them, and every step down the tree is likely a trip to a different part of memory.
+/// Here all of the nodes live side by side in one `Vec`, and children are `u32` indices into it
+/// rather than pointers. That means one allocation (well, a few as the `Vec` grows) instead of
+/// one per value, nodes half the size on a 64 bit machine, and far better odds that the next node
+/// is already in the cache.
+///
+/// It also means there is nothing to recurse through when the tree is dropped, the `Vec` just
+/// goes away.
+///
+/// Values are never removed, which keeps the indices simple: a node's index never changes.
+///
+/// Apart from that it has the same API as `BTree` for putting values in, looking them up and
+/// walking the tree:
+/// * `insert` and `insert_balanced`, plus `FromIterator` and `Extend`.
+/// * `contains`, `min`/`max`, `floor`/`ceiling`, `predecessor`/`successor`, `rank`/`select` and
+///   `range`. Nodes cache the size of their sub-tree as well as its height for `rank` and
+///   `select`, like `BTree`'s do.
+/// * `iter` (which goes both ways), `pre_order`, `post_order`, `level_order`, and `into_iter`
+///   which hands the values back in order.
+///
+/// ```
+/// # use exercises::btree::ArenaTree;
+/// let mut t = ArenaTree::empty();
+/// for val in [4, 2, 6, 1, 3, 5, 7] {
+///     t.insert(val);
+/// }
+///
+/// assert_eq!(t.contains(&5), true);
+/// assert_eq!(t.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4, &5, &6, &7]);
+/// assert_eq!(t.depth(), 3);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct ArenaTree<T> {
+    nodes: Vec<ArenaNode<T>>,
+    root: u32,
+}
+
+/// Stands in for a missing child (or an empty tree's root).
+const NIL: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+struct ArenaNode<T> {
+    value: T,
+    height: u32,
+    size: u32,
+    left: u32,
+    right: u32,
+}
+
+impl<T: Ord> Default for ArenaTree<T> {
+    fn default() -> Self {
+        ArenaTree::empty()
+    }
+}
+
+impl<T: Ord> ArenaTree<T> {
+    pub fn new(val: T) -> ArenaTree<T> {
+        let mut tree = ArenaTree::empty();
+        tree.insert(val);
+        tree
+    }
+
+    pub fn empty() -> ArenaTree<T> {
+        ArenaTree::with_capacity(0)
+    }
+
+    /// An empty tree with room for `capacity` values before the arena has to grow.
+    pub fn with_capacity(capacity: usize) -> ArenaTree<T> {
+        ArenaTree {
+            nodes: Vec::with_capacity(capacity),
+            root: NIL,
+        }
+    }
+
+    /// Nodes are never removed, so this is just how big the arena is.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn depth(&self) -> isize {
+        self.height(self.root) as isize
+    }
+
+    /// Determines if the root is balanced, the same as `BTree::balanced`.
+    pub fn balanced(&self) -> bool {
+        self.root == NIL || self.balance_factor(self.root).abs() <= 1
+    }
+
+    /// Insert a value into the tree, without any balancing, the same as `BTree::insert`.
+    ///
+    /// We walk down remembering the path, add the new node to the end of the arena, and then go
+    /// back along the path fixing up heights.
+    ///
+    /// Panics if the tree would hold more than `u32::MAX - 1` values.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let mut t = ArenaTree::empty();
+    /// for val in 1..=5 {
+    ///     t.insert(val);
+    /// }
+    ///
+    /// assert_eq!(t.depth(), 5);
+    /// assert_eq!(t.balanced(), false);
+    /// ```
+    pub fn insert(&mut self, val: T) {
+        self.insert_with(val, false);
+    }
+
+    /// Insert a value into the tree, keeping the tree balanced, the same as
+    /// `BTree::insert_balanced`.
+    ///
+    /// Rotations are a matter of swapping a few indices around, no node moves in the arena.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let mut t = ArenaTree::empty();
+    /// for val in 1..=7 {
+    ///     t.insert_balanced(val);
+    /// }
+    ///
+    /// assert_eq!(t.depth(), 3);
+    /// assert_eq!(t.balanced(), true);
+    /// ```
+    pub fn insert_balanced(&mut self, val: T) {
+        self.insert_with(val, true);
+    }
+
+    /// Is `val` in the tree?
+    pub fn contains(&self, val: &T) -> bool {
+        let mut index = self.root;
+        while index != NIL {
+            let node = &self.nodes[index as usize];
+            index = match val.cmp(&node.value) {
+                Ordering::Equal => return true,
+                Ordering::Less => node.left,
+                Ordering::Greater => node.right,
+            };
+        }
+        false
+    }
+
+    /// The smallest value in the tree.
+    pub fn min(&self) -> Option<&T> {
+        self.last_along(self.root, |node| node.left)
+    }
+
+    /// The largest value in the tree.
+    pub fn max(&self) -> Option<&T> {
+        self.last_along(self.root, |node| node.right)
+    }
+
+    /// The largest value in the tree which is less than or equal to `val`, see `BTree::floor`.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let t: ArenaTree<i32> = vec![10, 5, 15].into_iter().collect();
+    ///
+    /// assert_eq!(t.floor(&12), Some(&10));
+    /// assert_eq!(t.floor(&15), Some(&15));
+    /// assert_eq!(t.floor(&4), None);
+    /// ```
+    pub fn floor(&self, val: &T) -> Option<&T> {
+        self.closest(|other| val.cmp(other), true)
+    }
+
+    /// The smallest value in the tree which is greater than or equal to `val`, see
+    /// `BTree::ceiling`.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let t: ArenaTree<i32> = vec![10, 5, 15].into_iter().collect();
+    ///
+    /// assert_eq!(t.ceiling(&12), Some(&15));
+    /// assert_eq!(t.ceiling(&16), None);
+    /// ```
+    pub fn ceiling(&self, val: &T) -> Option<&T> {
+        self.closest(|other| val.cmp(other), false)
+    }
+
+    /// The largest value in the tree which is strictly less than `val`, see
+    /// `BTree::predecessor`.
+    pub fn predecessor(&self, val: &T) -> Option<&T> {
+        self.closest(|other| val.cmp(other).then(Ordering::Less), true)
+    }
+
+    /// The smallest value in the tree which is strictly greater than `val`, see
+    /// `BTree::successor`.
+    pub fn successor(&self, val: &T) -> Option<&T> {
+        self.closest(|other| val.cmp(other).then(Ordering::Greater), false)
+    }
+
+    /// The number of values in the tree which are less than `val`, see `BTree::rank`.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let t: ArenaTree<i32> = vec![50, 20, 80, 10, 30].into_iter().collect();
+    ///
+    /// assert_eq!(t.rank(&10), 0);
+    /// assert_eq!(t.rank(&31), 3);
+    /// assert_eq!(t.rank(&100), 5);
+    /// ```
+    pub fn rank(&self, val: &T) -> usize {
+        let mut rank = 0;
+        let mut index = self.root;
+
+        while index != NIL {
+            let node = &self.nodes[index as usize];
+            if node.value < *val {
+                rank += 1 + self.size(node.left) as usize;
+                index = node.right;
+            } else {
+                index = node.left;
+            }
+        }
+
+        rank
+    }
+
+    /// The `k`-th smallest value in the tree, counting from 0, see `BTree::select`.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let t: ArenaTree<i32> = vec![50, 20, 80, 10, 30].into_iter().collect();
+    ///
+    /// assert_eq!(t.select(0), Some(&10));
+    /// assert_eq!(t.select(4), Some(&80));
+    /// assert_eq!(t.select(5), None);
+    /// ```
+    pub fn select(&self, k: usize) -> Option<&T> {
+        let mut k = k;
+        let mut index = self.root;
+
+        while index != NIL {
+            let node = &self.nodes[index as usize];
+            let left = self.size(node.left) as usize;
+            match k.cmp(&left) {
+                Ordering::Less => index = node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    index = node.right;
+                },
+            }
+        }
+
+        None
+    }
+
+    /// Iterate, in sorted order, over just the values which fall inside `range`, see
+    /// `BTree::range`.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let t: ArenaTree<i32> = (1..=10).collect();
+    ///
+    /// assert_eq!(t.range(3..6).collect::<Vec<_>>(), vec![&3, &4, &5]);
+    /// assert_eq!(t.range(8..).rev().collect::<Vec<_>>(), vec![&10, &9, &8]);
+    /// ```
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> ArenaRange<'_, T> {
+        let mut iter = ArenaRange {
+            tree: self,
+            front: Vec::new(),
+            back: Vec::new(),
+            finished: false,
+        };
+
+        let mut index = self.root;
+        while index != NIL {
+            let node = &self.nodes[index as usize];
+            if after_start(range.start_bound(), &node.value) {
+                iter.front.push(index);
+                index = node.left;
+            } else {
+                index = node.right;
+            }
+        }
+
+        let mut index = self.root;
+        while index != NIL {
+            let node = &self.nodes[index as usize];
+            if before_end(range.end_bound(), &node.value) {
+                iter.back.push(index);
+                index = node.right;
+            } else {
+                index = node.left;
+            }
+        }
+
+        iter.finished = match iter.front.last() {
+            Some(&first) => !before_end(range.end_bound(), &self.nodes[first as usize].value),
+            None => true,
+        };
+
+        iter
+    }
+
+    ///
+    /// Private API: closest
+    ///
+    /// The same walk as `BTree`'s, behind `floor`, `ceiling`, `predecessor` and `successor`.
+    ///
+    fn closest<F>(&self, cmp: F, below: bool) -> Option<&T>
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let mut best = None;
+        let mut index = self.root;
+
+        while index != NIL {
+            let node = &self.nodes[index as usize];
+            match cmp(&node.value) {
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Less => {
+                    if !below {
+                        best = Some(&node.value);
+                    }
+                    index = node.left;
+                },
+                Ordering::Greater => {
+                    if below {
+                        best = Some(&node.value);
+                    }
+                    index = node.right;
+                },
+            }
+        }
+
+        best
+    }
+
+    ///
+    /// Private API: insert_with
+    ///
+    /// The shared guts of `insert` and `insert_balanced`.
+    ///
+    fn insert_with(&mut self, val: T, rebalance: bool) {
+        let mut path = Vec::new();
+        let mut index = self.root;
+
+        while index != NIL {
+            let node = &self.nodes[index as usize];
+            let next = match val.cmp(&node.value) {
+                // Duplicate values are not stored
+                Ordering::Equal => return,
+                Ordering::Less => node.left,
+                Ordering::Greater => node.right,
+            };
+            path.push(index);
+            index = next;
+        }
+
+        let new = u32::try_from(self.nodes.len())
+            .ok()
+            .filter(|&new| new != NIL)
+            .expect("an ArenaTree can't hold more than u32::MAX - 1 values");
+
+        let goes_left = path.last().map(|&parent| val < self.nodes[parent as usize].value);
+        self.nodes.push(ArenaNode {
+            value: val,
+            height: 1,
+            size: 1,
+            left: NIL,
+            right: NIL,
+        });
+
+        match (path.last(), goes_left) {
+            (Some(&parent), Some(true)) => self.nodes[parent as usize].left = new,
+            (Some(&parent), _) => self.nodes[parent as usize].right = new,
+            (None, _) => self.root = new,
+        }
+
+        // Back up the path. A rebalance can put a different node at the top of a sub-tree, in
+        // which case its parent has to point at the new one.
+        while let Some(index) = path.pop() {
+            let top = if rebalance {
+                self.rebalance(index)
+            } else {
+                self.update(index);
+                index
+            };
+
+            if top != index {
+                match path.last() {
+                    Some(&parent) => {
+                        let parent = &mut self.nodes[parent as usize];
+                        if parent.left == index {
+                            parent.left = top;
+                        } else {
+                            parent.right = top;
+                        }
+                    },
+                    None => self.root = top,
+                }
+            }
+        }
+    }
+
+    ///
+    /// Private API: rebalance
+    ///
+    /// The same four cases as `Node::rebalance`, returning whichever node ends up on top.
+    ///
+    fn rebalance(&mut self, index: u32) -> u32 {
+        self.update(index);
+
+        let factor = self.balance_factor(index);
+        if factor > 1 {
+            let left = self.nodes[index as usize].left;
+            if self.balance_factor(left) < 0 {
+                self.nodes[index as usize].left = self.rotate_left(left);
+            }
+            self.rotate_right(index)
+        } else if factor < -1 {
+            let right = self.nodes[index as usize].right;
+            if self.balance_factor(right) > 0 {
+                self.nodes[index as usize].right = self.rotate_right(right);
+            }
+            self.rotate_left(index)
+        } else {
+            index
+        }
+    }
+}
+
+impl<T> ArenaTree<T> {
+    /// Iterate over the values in sorted (in-order) order, from either end.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let t: ArenaTree<i32> = vec![2, 1, 3].into_iter().collect();
+    ///
+    /// assert_eq!(t.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// assert_eq!(t.iter().rev().collect::<Vec<_>>(), vec![&3, &2, &1]);
+    /// ```
+    pub fn iter(&self) -> ArenaIter<'_, T> {
+        let mut iter = ArenaIter {
+            tree: self,
+            front: Vec::new(),
+            back: Vec::new(),
+            remaining: self.nodes.len(),
+        };
+        iter.push_left(self.root);
+        iter.push_right(self.root);
+        iter
+    }
+
+    /// Iterate over the values node first, then the left sub-tree, then the right sub-tree.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let mut t = ArenaTree::empty();
+    /// for val in [4, 2, 6, 1, 3, 5, 7] {
+    ///     t.insert(val);
+    /// }
+    ///
+    /// assert_eq!(t.pre_order().collect::<Vec<_>>(), vec![&4, &2, &1, &3, &6, &5, &7]);
+    /// ```
+    pub fn pre_order(&self) -> ArenaPreOrder<'_, T> {
+        ArenaPreOrder {
+            tree: self,
+            stack: self.existing(self.root).collect(),
+        }
+    }
+
+    /// Iterate over the values left sub-tree first, then the right sub-tree, then the node.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let mut t = ArenaTree::empty();
+    /// for val in [4, 2, 6, 1, 3, 5, 7] {
+    ///     t.insert(val);
+    /// }
+    ///
+    /// assert_eq!(t.post_order().collect::<Vec<_>>(), vec![&1, &3, &2, &5, &7, &6, &4]);
+    /// ```
+    pub fn post_order(&self) -> ArenaPostOrder<'_, T> {
+        ArenaPostOrder {
+            tree: self,
+            stack: self.existing(self.root).map(|index| (index, false)).collect(),
+        }
+    }
+
+    /// Iterate over the values breadth first, one level of the tree at a time.
+    ///
+    /// ```
+    /// # use exercises::btree::ArenaTree;
+    /// let mut t = ArenaTree::empty();
+    /// for val in [4, 2, 6, 1, 3, 5, 7] {
+    ///     t.insert(val);
+    /// }
+    ///
+    /// assert_eq!(t.level_order().collect::<Vec<_>>(), vec![&4, &2, &6, &1, &3, &5, &7]);
+    /// ```
+    pub fn level_order(&self) -> ArenaLevelOrder<'_, T> {
+        ArenaLevelOrder {
+            tree: self,
+            queue: self.existing(self.root).collect(),
+        }
+    }
+
+    fn height(&self, index: u32) -> u32 {
+        if index == NIL {
+            0
+        } else {
+            self.nodes[index as usize].height
+        }
+    }
+
+    fn size(&self, index: u32) -> u32 {
+        if index == NIL {
+            0
+        } else {
+            self.nodes[index as usize].size
+        }
+    }
+
+    fn balance_factor(&self, index: u32) -> i64 {
+        let node = &self.nodes[index as usize];
+        self.height(node.left) as i64 - self.height(node.right) as i64
+    }
+
+    ///
+    /// Private API: update
+    ///
+    /// Re-calculates a node's cached height and size from its children.
+    ///
+    fn update(&mut self, index: u32) {
+        let node = &self.nodes[index as usize];
+        let height = 1 + std::cmp::max(self.height(node.left), self.height(node.right));
+        let size = 1 + self.size(node.left) + self.size(node.right);
+        let node = &mut self.nodes[index as usize];
+        node.height = height;
+        node.size = size;
+    }
+
+    ///
+    /// Private API: rotate_right
+    ///
+    /// Lifts the left child of `index` above it, returning the left child's index, which is now
+    /// the top of this sub-tree. The caller has to point the parent at it.
+    ///
+    fn rotate_right(&mut self, index: u32) -> u32 {
+        let pivot = self.nodes[index as usize].left;
+        self.nodes[index as usize].left = self.nodes[pivot as usize].right;
+        self.nodes[pivot as usize].right = index;
+        self.update(index);
+        self.update(pivot);
+        pivot
+    }
+
+    ///
+    /// Private API: rotate_left
+    ///
+    /// The mirror image of `rotate_right`.
+    ///
+    fn rotate_left(&mut self, index: u32) -> u32 {
+        let pivot = self.nodes[index as usize].right;
+        self.nodes[index as usize].right = self.nodes[pivot as usize].left;
+        self.nodes[pivot as usize].left = index;
+        self.update(index);
+        self.update(pivot);
+        pivot
+    }
+
+    fn last_along<F>(&self, index: u32, next: F) -> Option<&T>
+    where
+        F: Fn(&ArenaNode<T>) -> u32,
+    {
+        if index == NIL {
+            return None;
+        }
+
+        let mut node = &self.nodes[index as usize];
+        while next(node) != NIL {
+            node = &self.nodes[next(node) as usize];
+        }
+        Some(&node.value)
+    }
+
+    /// `index` itself, if it isn't `NIL`. Handy for seeding a stack or queue.
+    fn existing(&self, index: u32) -> impl Iterator<Item = u32> {
+        Some(index).filter(|&index| index != NIL).into_iter()
+    }
+}
+
+///
+/// In-order iterator over an [ArenaTree], the same two stack walk as [Iter](super::Iter), but
+/// with indices.
+///
+pub struct ArenaIter<'a, T> {
+    tree: &'a ArenaTree<T>,
+    front: Vec<u32>,
+    back: Vec<u32>,
+    remaining: usize,
+}
+
+impl<'a, T> ArenaIter<'a, T> {
+    fn push_left(&mut self, mut index: u32) {
+        while index != NIL {
+            self.front.push(index);
+            index = self.tree.nodes[index as usize].left;
+        }
+    }
+
+    fn push_right(&mut self, mut index: u32) {
+        while index != NIL {
+            self.back.push(index);
+            index = self.tree.nodes[index as usize].right;
+        }
+    }
+}
+
+impl<'a, T> Iterator for ArenaIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = &self.tree.nodes[self.front.pop()? as usize];
+        self.push_left(node.right);
+        self.remaining -= 1;
+
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for ArenaIter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = &self.tree.nodes[self.back.pop()? as usize];
+        self.push_right(node.left);
+        self.remaining -= 1;
+
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for ArenaIter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a ArenaTree<T> {
+    type Item = &'a T;
+    type IntoIter = ArenaIter<'a, T>;
+
+    fn into_iter(self) -> ArenaIter<'a, T> {
+        self.iter()
+    }
+}
+
+///
+/// Pre-order iterator over an [ArenaTree].
+///
+pub struct ArenaPreOrder<'a, T> {
+    tree: &'a ArenaTree<T>,
+    stack: Vec<u32>,
+}
+
+impl<'a, T> Iterator for ArenaPreOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = &self.tree.nodes[self.stack.pop()? as usize];
+        self.stack.extend(self.tree.existing(node.right));
+        self.stack.extend(self.tree.existing(node.left));
+        Some(&node.value)
+    }
+}
+
+///
+/// Post-order iterator over an [ArenaTree], marking nodes as expanded the same as
+/// [PostOrder](super::PostOrder).
+///
+pub struct ArenaPostOrder<'a, T> {
+    tree: &'a ArenaTree<T>,
+    stack: Vec<(u32, bool)>,
+}
+
+impl<'a, T> Iterator for ArenaPostOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (index, expanded) = self.stack.pop()?;
+            let node = &self.tree.nodes[index as usize];
+
+            if expanded {
+                return Some(&node.value);
+            }
+
+            self.stack.push((index, true));
+            self.stack.extend(self.tree.existing(node.right).map(|index| (index, false)));
+            self.stack.extend(self.tree.existing(node.left).map(|index| (index, false)));
+        }
+    }
+}
+
+///
+/// Level-order iterator over an [ArenaTree].
+///
+pub struct ArenaLevelOrder<'a, T> {
+    tree: &'a ArenaTree<T>,
+    queue: VecDeque<u32>,
+}
+
+impl<'a, T> Iterator for ArenaLevelOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = &self.tree.nodes[self.queue.pop_front()? as usize];
+        self.queue.extend(self.tree.existing(node.left));
+        self.queue.extend(self.tree.existing(node.right));
+        Some(&node.value)
+    }
+}
+
+///
+/// Iterator over the values of an [ArenaTree] inside a range, created by [ArenaTree::range].
+///
+/// The same as [Range](super::Range): two stacks which stop when they run into each other.
+///
+pub struct ArenaRange<'a, T> {
+    tree: &'a ArenaTree<T>,
+    front: Vec<u32>,
+    back: Vec<u32>,
+    finished: bool,
+}
+
+impl<'a, T> Iterator for ArenaRange<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.finished {
+            return None;
+        }
+
+        let index = self.front.pop()?;
+
+        if self.back.last() == Some(&index) {
+            self.finished = true;
+        } else {
+            let mut next = self.tree.nodes[index as usize].right;
+            while next != NIL {
+                self.front.push(next);
+                next = self.tree.nodes[next as usize].left;
+            }
+        }
+
+        Some(&self.tree.nodes[index as usize].value)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for ArenaRange<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.finished {
+            return None;
+        }
+
+        let index = self.back.pop()?;
+
+        if self.front.last() == Some(&index) {
+            self.finished = true;
+        } else {
+            let mut next = self.tree.nodes[index as usize].left;
+            while next != NIL {
+                self.back.push(next);
+                next = self.tree.nodes[next as usize].right;
+            }
+        }
+
+        Some(&self.tree.nodes[index as usize].value)
+    }
+}
+
+///
+/// Owning in-order iterator over an [ArenaTree].
+///
+/// The values are stored in the arena in the order they were inserted, not sorted order. So
+/// first we walk the tree to find out where each node comes in sorted order, then move every
+/// value straight to its spot in a new `Vec`, and hand them out from there.
+///
+/// ```
+/// # use exercises::btree::ArenaTree;
+/// let t: ArenaTree<String> = vec!["b", "c", "a"].into_iter().map(String::from).collect();
+///
+/// let values: Vec<String> = t.into_iter().collect();
+/// assert_eq!(values, vec!["a", "b", "c"]);
+/// ```
+///
+pub struct ArenaIntoIter<T> {
+    values: std::vec::IntoIter<T>,
+}
+
+impl<T> Iterator for ArenaIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.values.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for ArenaIntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.values.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for ArenaIntoIter<T> {}
+
+impl<T> IntoIterator for ArenaTree<T> {
+    type Item = T;
+    type IntoIter = ArenaIntoIter<T>;
+
+    fn into_iter(self) -> ArenaIntoIter<T> {
+        let mut position = vec![0; self.nodes.len()];
+        let mut stack = Vec::new();
+        let mut index = self.root;
+        let mut next = 0;
+        loop {
+            while index != NIL {
+                stack.push(index);
+                index = self.nodes[index as usize].left;
+            }
+            match stack.pop() {
+                Some(top) => {
+                    position[top as usize] = next;
+                    next += 1;
+                    index = self.nodes[top as usize].right;
+                },
+                None => break,
+            }
+        }
+
+        let mut sorted: Vec<Option<T>> = self.nodes.iter().map(|_| None).collect();
+        for (index, node) in self.nodes.into_iter().enumerate() {
+            sorted[position[index]] = Some(node.value);
+        }
+
+        ArenaIntoIter {
+            values: sorted
+                .into_iter()
+                .map(|value| value.expect("every node has a spot in sorted order"))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        }
+    }
+}
+
+///
+/// Collecting into an arena tree inserts with `insert_balanced`, leaving room for everything up
+/// front if the iterator knows how much is coming.
+///
+impl<T: Ord> FromIterator<T> for ArenaTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> ArenaTree<T> {
+        let iter = iter.into_iter();
+        let mut tree = ArenaTree::with_capacity(iter.size_hint().0);
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for ArenaTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert_balanced(val);
+        }
+    }
+}
+
+#[test]
+fn test_arena_tree() {
+    use super::BTree;
+    use rand::prelude::*;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-1000, 1000);
+
+    let values = (0..1024).map(|_| rng.sample(distr)).collect::<Vec<i32>>();
+
+    // Same values, same order, same shape as the boxed tree, both with and without balancing.
+    for rebalance in [false, true] {
+        let mut arena = ArenaTree::empty();
+        let mut boxed = BTree::empty();
+        for &val in values.iter() {
+            if rebalance {
+                arena.insert_balanced(val);
+                boxed.insert_balanced(val);
+            } else {
+                arena.insert(val);
+                boxed.insert(val);
+            }
+        }
+
+        assert_eq!(arena.len(), boxed.len());
+        assert_eq!(arena.depth(), boxed.depth());
+        assert_eq!(arena.balanced(), boxed.balanced());
+        assert_eq!(arena.min(), boxed.min());
+        assert_eq!(arena.max(), boxed.max());
+        assert!(arena.iter().eq(boxed.iter()));
+        assert!(arena.iter().rev().eq(boxed.iter().rev()));
+        assert_eq!(arena.iter().len(), boxed.len());
+        assert!(arena.pre_order().eq(boxed.pre_order()));
+        assert!(arena.post_order().eq(boxed.post_order()));
+        assert!(arena.level_order().eq(boxed.level_order()));
+
+        for val in -1000..=1000 {
+            assert_eq!(arena.contains(&val), boxed.contains(&val));
+            assert_eq!(arena.floor(&val), boxed.floor(&val));
+            assert_eq!(arena.ceiling(&val), boxed.ceiling(&val));
+            assert_eq!(arena.predecessor(&val), boxed.predecessor(&val));
+            assert_eq!(arena.successor(&val), boxed.successor(&val));
+            assert_eq!(arena.rank(&val), boxed.rank(&val));
+        }
+
+        for k in 0..=arena.len() {
+            assert_eq!(arena.select(k), boxed.select(k));
+        }
+
+        for _ in 0..64 {
+            let (a, b) = (rng.sample(distr), rng.sample(distr));
+            let (low, high) = (a.min(b), a.max(b));
+            assert!(arena.range(low..high).eq(boxed.range(low..high)));
+            assert!(arena.range(low..=high).rev().eq(boxed.range(low..=high).rev()));
+            assert!(arena.range(..high).eq(boxed.range(..high)));
+            assert!(arena.range(low..).rev().eq(boxed.range(low..).rev()));
+        }
+
+        assert!(arena.into_iter().eq(boxed.into_iter()));
+    }
+
+    // Taking values from both ends of the iterator stops where they meet.
+    let t: ArenaTree<i32> = (1..=5).collect();
+    let mut iter = t.iter();
+    assert_eq!(iter.next(), Some(&1));
+    assert_eq!(iter.next_back(), Some(&5));
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.collect::<Vec<_>>(), vec![&2, &3, &4]);
+    assert_eq!(t.range(2..2).next(), None);
+
+    let empty: ArenaTree<i32> = ArenaTree::empty();
+    assert_eq!(empty.depth(), 0);
+    assert!(empty.balanced());
+    assert_eq!(empty.min(), None);
+    assert_eq!(empty.iter().next(), None);
+    assert_eq!(empty.pre_order().next(), None);
+    assert_eq!(empty.post_order().next(), None);
+    assert_eq!(empty.level_order().next(), None);
+    assert_eq!(empty.range(..).next(), None);
+    assert_eq!(empty.select(0), None);
+    assert_eq!(empty.floor(&0), None);
+    assert_eq!(empty.into_iter().next(), None);
+
+    // A vine which would overflow the stack if anything here recursed.
+    let mut t = ArenaTree::with_capacity(10_000);
+    for val in 0..10_000 {
+        t.insert(val);
+    }
+    assert_eq!(t.depth(), 10_000);
+    assert!(t.contains(&9_999));
+    assert!(t.iter().copied().eq(0..10_000));
+    assert!(t.iter().rev().copied().eq((0..10_000).rev()));
+    assert!(t.post_order().copied().eq((0..10_000).rev()));
+    assert_eq!(t.rank(&5_000), 5_000);
+    assert_eq!(t.select(9_999), Some(&9_999));
+    assert!(t.into_iter().eq(0..10_000));
+}
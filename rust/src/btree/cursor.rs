@@ -0,0 +1,386 @@
+use super::{take_node, BTree, Direction, Node};
+
+impl<T: Ord> BTree<T> {
+    /// A cursor for walking around the tree node by node and editing it as you go, starting at
+    /// the root.
+    ///
+    /// The tree is borrowed for as long as the cursor is around, and is put back together when
+    /// the cursor is dropped.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree: BTree<i32> = (1..=7).collect();
+    ///
+    /// {
+    ///     let mut cursor = my_tree.cursor_mut();
+    ///     assert_eq!(cursor.current(), Some(&4));
+    ///
+    ///     cursor.move_left();
+    ///     cursor.move_next();
+    ///     assert_eq!(cursor.current(), Some(&3));
+    ///
+    ///     assert_eq!(cursor.remove(), Some(3));
+    ///     assert_eq!(cursor.current(), Some(&2));
+    /// }
+    ///
+    /// assert_eq!(my_tree.iter().collect::<Vec<_>>(), vec![&1, &2, &4, &5, &6, &7]);
+    /// ```
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        let focus = self.root.take();
+        CursorMut {
+            tree: self,
+            focus,
+            path: Vec::new(),
+        }
+    }
+}
+
+///
+/// CursorMut
+///
+/// A position in a [BTree] which can move around and edit the tree in place, created by
+/// [BTree::cursor_mut].
+///
+/// Nodes don't know their parents, so there's no way to go back up from a `&mut Node`. Instead
+/// this is a "zipper": going down into a child, we cut the child off of its parent and keep the
+/// parent (and which way we went) on a stack. The node we're on is the root of a little tree of
+/// its own, and everything above it is on the stack. Going back up pops the parent off the stack
+/// and re-attaches the child.
+///
+/// So every move is O(1), `move_next` and `move_prev` are O(1) on average over a whole walk,
+/// and editing the node we're on never needs another search from the root.
+///
+/// Nodes on the stack are missing a child, so their cached height and size go stale while we're
+/// below them. Each one is brought up to date as it's re-attached on the way back up, which
+/// always happens by the time the cursor is dropped.
+///
+/// Like `insert` and `remove`, edits through a cursor do no rebalancing.
+///
+pub struct CursorMut<'a, T: Ord> {
+    tree: &'a mut BTree<T>,
+    focus: Option<Box<Node<T>>>,
+    path: Vec<(Box<Node<T>>, Direction)>,
+}
+
+impl<'a, T: Ord> CursorMut<'a, T> {
+    /// The value at the cursor, or `None` if the tree is empty.
+    pub fn current(&self) -> Option<&T> {
+        self.focus.as_ref().map(|node| &node.value)
+    }
+
+    /// Is the cursor at the root?
+    pub fn is_root(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// Move to the left child, returning whether there was one to move to.
+    pub fn move_left(&mut self) -> bool {
+        self.move_down(Direction::Left)
+    }
+
+    /// Move to the right child, returning whether there was one to move to.
+    pub fn move_right(&mut self) -> bool {
+        self.move_down(Direction::Right)
+    }
+
+    /// Move up to the parent, returning whether there was one (we weren't at the root).
+    pub fn move_parent(&mut self) -> bool {
+        let (mut parent, direction) = match self.path.pop() {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        match direction {
+            Direction::Left => parent.left = self.focus.take(),
+            Direction::Right => parent.right = self.focus.take(),
+        }
+        parent.update();
+
+        self.focus = Some(parent);
+        true
+    }
+
+    /// Move to the next value in sorted order, returning whether there was one.
+    ///
+    /// If the node has a right sub-tree, the next value is the left-most node in there.
+    /// Otherwise it's the first ancestor we are to the left of, so we go up until we come up out
+    /// of a left child.
+    ///
+    /// If we come out of the top of the tree instead, we were at the biggest value, so we go back
+    /// down to where we started and stay put.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree: BTree<i32> = vec![4, 2, 6, 1, 3, 5, 7].into_iter().collect();
+    /// let mut cursor = my_tree.cursor_mut();
+    ///
+    /// while cursor.move_prev() {}
+    ///
+    /// let mut seen = vec![*cursor.current().unwrap()];
+    /// while cursor.move_next() {
+    ///     seen.push(*cursor.current().unwrap());
+    /// }
+    ///
+    /// assert_eq!(seen, vec![1, 2, 3, 4, 5, 6, 7]);
+    /// assert_eq!(cursor.current(), Some(&7));
+    /// ```
+    pub fn move_next(&mut self) -> bool {
+        self.step(Direction::Right)
+    }
+
+    /// Move to the previous value in sorted order, returning whether there was one.
+    ///
+    /// The mirror image of `move_next`.
+    pub fn move_prev(&mut self) -> bool {
+        self.step(Direction::Left)
+    }
+
+    /// Insert a value into the sub-tree under the cursor, returning whether it was added.
+    ///
+    /// The cursor stays where it is, the new value goes in below it wherever it belongs, just like
+    /// `BTree::insert` would put it in a tree starting from here.
+    ///
+    /// Not every value can go under every node though. Everything below the left child of `5` has
+    /// to be less than `5`, so `7` can't be inserted there. Values which don't belong here, and
+    /// duplicates, are not added.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree: BTree<i32> = vec![5, 2, 8].into_iter().collect();
+    /// let mut cursor = my_tree.cursor_mut();
+    ///
+    /// cursor.move_left();
+    /// assert_eq!(cursor.current(), Some(&2));
+    ///
+    /// assert_eq!(cursor.insert(3), true);
+    /// assert_eq!(cursor.insert(7), false);
+    /// assert_eq!(cursor.insert(2), false);
+    ///
+    /// drop(cursor);
+    /// assert_eq!(my_tree.iter().collect::<Vec<_>>(), vec![&2, &3, &5, &8]);
+    /// ```
+    pub fn insert(&mut self, val: T) -> bool {
+        if !self.fits(&val) {
+            return false;
+        }
+
+        match self.focus {
+            Some(ref mut node) => node.insert(val),
+            None => {
+                self.focus = Some(Box::new(Node::new(val)));
+                true
+            },
+        }
+    }
+
+    /// Remove the value at the cursor and return it.
+    ///
+    /// The node is unlinked the same way `BTree::remove` does it, and the cursor moves to
+    /// whichever node took its place. If nothing did (it was a leaf) the cursor moves up to the
+    /// parent instead.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree: BTree<i32> = vec![5, 2, 8].into_iter().collect();
+    /// let mut cursor = my_tree.cursor_mut();
+    ///
+    /// assert_eq!(cursor.remove(), Some(5));
+    /// assert_eq!(cursor.current(), Some(&8));
+    ///
+    /// cursor.move_left();
+    /// assert_eq!(cursor.remove(), Some(2));
+    /// assert_eq!(cursor.current(), Some(&8));
+    /// ```
+    pub fn remove(&mut self) -> Option<T> {
+        let removed = take_node(&mut self.focus, false, &mut self.tree.rotations)?;
+        if self.focus.is_none() {
+            self.move_parent();
+        }
+        Some(removed)
+    }
+
+    fn move_down(&mut self, direction: Direction) -> bool {
+        let mut node = match self.focus.take() {
+            Some(node) => node,
+            None => return false,
+        };
+
+        let child = match direction {
+            Direction::Left => node.left.take(),
+            Direction::Right => node.right.take(),
+        };
+
+        match child {
+            Some(child) => {
+                self.path.push((node, direction));
+                self.focus = Some(child);
+                true
+            },
+            None => {
+                self.focus = Some(node);
+                false
+            },
+        }
+    }
+
+    ///
+    /// Private API: step
+    ///
+    /// `move_next` when `forward` is `Right`, `move_prev` when it's `Left`.
+    ///
+    fn step(&mut self, forward: Direction) -> bool {
+        let back = match forward {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        };
+
+        if self.move_down(forward) {
+            while self.move_down(back) {}
+            return true;
+        }
+
+        let mut climbed = Vec::new();
+        while let Some(&(_, direction)) = self.path.last() {
+            self.move_parent();
+            if direction == back {
+                return true;
+            }
+            climbed.push(direction);
+        }
+
+        // No next value, retrace our steps.
+        for direction in climbed.into_iter().rev() {
+            self.move_down(direction);
+        }
+        false
+    }
+
+    ///
+    /// Private API: fits
+    ///
+    /// Could `val` go somewhere under the cursor without breaking the ordering?
+    ///
+    /// The closest ancestor we went left from is the tightest upper bound, and the closest we
+    /// went right from is the tightest lower bound, so those are the only two we need to check.
+    ///
+    fn fits(&self, val: &T) -> bool {
+        let upper = self.path.iter().rev().find(|(_, direction)| *direction == Direction::Left);
+        let lower = self.path.iter().rev().find(|(_, direction)| *direction == Direction::Right);
+
+        upper.is_none_or(|(node, _)| *val < node.value)
+            && lower.is_none_or(|(node, _)| *val > node.value)
+    }
+}
+
+///
+/// Zips the tree back up and hands it back.
+///
+impl<'a, T: Ord> Drop for CursorMut<'a, T> {
+    fn drop(&mut self) {
+        while self.move_parent() {}
+        self.tree.root = self.focus.take();
+    }
+}
+
+#[test]
+fn test_cursor_mut() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-200, 200);
+
+    let mut t = BTree::empty();
+    let mut oracle = BTreeSet::new();
+
+    // An empty tree, the cursor can only insert the first value.
+    {
+        let mut cursor = t.cursor_mut();
+        assert_eq!(cursor.current(), None);
+        assert!(!cursor.move_next());
+        assert!(!cursor.move_parent());
+        assert_eq!(cursor.remove(), None);
+        assert!(cursor.insert(0));
+        assert_eq!(cursor.current(), Some(&0));
+        oracle.insert(0);
+    }
+
+    for _ in 0..64 {
+        {
+            let mut cursor = t.cursor_mut();
+
+            for _ in 0..64 {
+                match rng.gen_range(0..7) {
+                    0 => {
+                        cursor.move_left();
+                    },
+                    1 => {
+                        cursor.move_right();
+                    },
+                    2 => {
+                        cursor.move_parent();
+                    },
+                    3 => {
+                        let before = cursor.current().copied();
+                        let expected =
+                            before.and_then(|val| oracle.range(val + 1..).next().copied());
+                        assert_eq!(cursor.move_next(), expected.is_some());
+                        assert_eq!(cursor.current().copied(), expected.or(before));
+                    },
+                    4 => {
+                        let before = cursor.current().copied();
+                        let expected =
+                            before.and_then(|val| oracle.range(..val).next_back().copied());
+                        assert_eq!(cursor.move_prev(), expected.is_some());
+                        assert_eq!(cursor.current().copied(), expected.or(before));
+                    },
+                    5 => {
+                        let val = rng.sample(distr);
+                        let fits = cursor.fits(&val);
+                        let inserted = cursor.insert(val);
+                        assert_eq!(inserted, fits && !oracle.contains(&val));
+                        if inserted {
+                            oracle.insert(val);
+                        }
+                    },
+                    _ => {
+                        if let Some(val) = cursor.remove() {
+                            assert!(oracle.remove(&val));
+                        }
+                    },
+                }
+            }
+        }
+
+        // Once the cursor is gone the tree is whole again, caches and all.
+        assert_eq!(t.validate(), Ok(()));
+        assert!(t.iter().eq(oracle.iter()));
+        assert_eq!(t.len(), oracle.len());
+
+        // Top it up so the cursor has somewhere to go.
+        for _ in 0..8 {
+            let val = rng.sample(distr);
+            t.insert(val);
+            oracle.insert(val);
+        }
+    }
+
+    // Walking a deep vine end to end and back.
+    let mut t = BTree::empty();
+    for val in 0..5_000 {
+        t.insert(val);
+    }
+    {
+        let mut cursor = t.cursor_mut();
+        let mut count = 1;
+        while cursor.move_next() {
+            count += 1;
+        }
+        assert_eq!(count, 5_000);
+        assert_eq!(cursor.current(), Some(&4_999));
+        while cursor.move_prev() {}
+        assert_eq!(cursor.current(), Some(&0));
+    }
+    assert_eq!(t.validate(), Ok(()));
+}
@@ -0,0 +1,303 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::{size, BTree, Node};
+
+///
+/// The ways [BTree::render] can draw a tree.
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Layout {
+    /// The style used in the docs, and what `Display` uses.
+    ///
+    /// The right child (the bigger one) goes on the same line after a ` -> `, the left child goes
+    /// on a line below, hanging off a `` `-> `` one column to the right of its parent (the root's
+    /// left child starts right at the margin).
+    /// A `|` marks a parent which still has a left child to come further down.
+    ///
+    /// ```text
+    /// 4 -> 6 -> 7
+    /// |     `-> 5
+    /// `-> 2 -> 3
+    ///      `-> 1
+    /// ```
+    Horizontal,
+    /// Root at the top, one row per level, values spaced out left to right in sorted order.
+    ///
+    /// ```text
+    ///    ___4___
+    ///   /       \
+    ///  _2_     _6_
+    /// /   \   /   \
+    /// 1   3   5   7
+    /// ```
+    TopDown,
+}
+
+///
+/// Prints the tree in the [Layout::Horizontal] style.
+///
+/// ```
+/// # use exercises::btree::BTree;
+/// let mut my_tree = BTree::new(7);
+/// for val in 1..7 {
+///     my_tree.insert(val);
+/// }
+///
+/// assert_eq!(my_tree.to_string(), "7\n`-> 1 -> 2 -> 3 -> 4 -> 5 -> 6");
+/// ```
+///
+impl<T: fmt::Display> fmt::Display for BTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(Layout::Horizontal))
+    }
+}
+
+impl<T: fmt::Display> BTree<T> {
+    /// Draw the tree as text in the given [Layout].
+    ///
+    /// Lines are separated by `\n`, trailing spaces are trimmed, and there is no newline at the
+    /// end. An empty tree renders as an empty string.
+    ///
+    /// ```
+    /// # use exercises::btree::{BTree, Layout};
+    /// let mut my_tree = BTree::new(2);
+    /// my_tree.insert(1);
+    /// my_tree.insert(3);
+    ///
+    /// assert_eq!(my_tree.render(Layout::Horizontal), "2 -> 3\n`-> 1");
+    /// assert_eq!(my_tree.render(Layout::TopDown), " _2_\n/   \\\n1   3");
+    /// ```
+    pub fn render(&self, layout: Layout) -> String {
+        match layout {
+            Layout::Horizontal => self.render_horizontal(),
+            Layout::TopDown => self.render_top_down(),
+        }
+    }
+
+    ///
+    /// Private API: render_horizontal
+    ///
+    /// Each line is a "chain" of right children.
+    /// As we write a chain we push the left child of every node in it onto a stack, along with
+    /// the column its branch hangs from: one to the right of where its parent was written, or
+    /// the margin for the root. Then each left child we pop starts a new line (and a new chain)
+    /// with a `` `-> `` in that column.
+    ///
+    /// Popping the stack means the last node in a chain gets its left child drawn first, which
+    /// keeps every sub-tree on the lines right below its parent. Everything still on the stack is
+    /// a parent waiting for its left child, so we draw a `|` in its column.
+    ///
+    fn render_horizontal(&self) -> String {
+        let mut lines = Vec::new();
+        let mut pending: Vec<(&Node<T>, usize)> = Vec::new();
+
+        if let Some(ref root) = self.root {
+            let mut line = Line::default();
+            line.chain(root, &mut pending);
+            lines.push(line.text);
+        }
+
+        while let Some((node, column)) = pending.pop() {
+            let mut line = Line::default();
+            for &(_, waiting) in pending.iter() {
+                line.pad(waiting);
+                line.push("|");
+            }
+            line.pad(column);
+            line.push("`-> ");
+            line.chain(node, &mut pending);
+            lines.push(line.text);
+        }
+
+        lines.join("\n")
+    }
+
+    ///
+    /// Private API: render_top_down
+    ///
+    /// Every value gets its own slice of columns, in sorted order, one space apart. That way a
+    /// node is always to the right of its whole left sub-tree and to the left of its whole right
+    /// sub-tree, and nothing overlaps.
+    ///
+    /// To know which slice a node gets we need its position in sorted order, which is exactly
+    /// what the cached sizes give us: a left child is `size(left.right) + 1` places before its
+    /// parent, and a right child is `size(right.left) + 1` places after.
+    ///
+    /// Then it is a breadth first walk, one row of values (with `_` leading out to the children)
+    /// and one row of `/` and `\` per level.
+    ///
+    fn render_top_down(&self) -> String {
+        let labels = self.iter().map(|val| val.to_string()).collect::<Vec<_>>();
+        let widths = labels.iter().map(|label| label.chars().count()).collect::<Vec<_>>();
+
+        let mut starts = Vec::with_capacity(widths.len());
+        let mut total = 0;
+        for width in widths.iter() {
+            starts.push(total);
+            total += width + 1;
+        }
+        let center = |index: usize| starts[index] + widths[index].saturating_sub(1) / 2;
+
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut queue = VecDeque::new();
+        if let Some(ref root) = self.root {
+            queue.push_back((&**root, size(&root.left), 0));
+        }
+
+        while let Some((node, index, level)) = queue.pop_front() {
+            if rows.len() < 2 * level + 2 {
+                rows.push(vec![' '; total]);
+                rows.push(vec![' '; total]);
+            }
+
+            let start = starts[index];
+            let end = start + widths[index];
+
+            for (offset, c) in labels[index].chars().enumerate() {
+                rows[2 * level][start + offset] = c;
+            }
+
+            if let Some(ref left) = node.left {
+                let child = index - size(&left.right) - 1;
+                let from = std::cmp::min(center(child) + 1, start);
+                rows[2 * level][from..start].fill('_');
+                rows[2 * level + 1][center(child)] = '/';
+                queue.push_back((&**left, child, level + 1));
+            }
+
+            if let Some(ref right) = node.right {
+                let child = index + size(&right.left) + 1;
+                let to = std::cmp::max(center(child), end);
+                rows[2 * level][end..to].fill('_');
+                rows[2 * level + 1][center(child)] = '\\';
+                queue.push_back((&**right, child, level + 1));
+            }
+        }
+
+        // The bottom level has no children, so its row of branches is blank.
+        rows.pop();
+
+        rows.iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+///
+/// One line of horizontal output, keeping track of how wide it is in characters (not bytes) so
+/// values with non-ASCII characters still line up.
+///
+#[derive(Default)]
+struct Line {
+    text: String,
+    width: usize,
+}
+
+impl Line {
+    fn push(&mut self, s: &str) {
+        self.text.push_str(s);
+        self.width += s.chars().count();
+    }
+
+    fn pad(&mut self, column: usize) {
+        while self.width < column {
+            self.push(" ");
+        }
+    }
+
+    fn chain<'a, T>(&mut self, node: &'a Node<T>, pending: &mut Vec<(&'a Node<T>, usize)>)
+    where
+        T: fmt::Display,
+    {
+        let mut node = node;
+        loop {
+            let column = self.width;
+            self.push(&node.value.to_string());
+
+            if let Some(ref left) = node.left {
+                let hang = if column == 0 { 0 } else { column + 1 };
+                pending.push((left, hang));
+            }
+
+            match node.right {
+                Some(ref right) => {
+                    self.push(" -> ");
+                    node = right;
+                },
+                None => break,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_display() {
+    let mut t = BTree::empty();
+
+    assert_eq!(t.to_string(), "");
+    assert_eq!(t.render(Layout::TopDown), "");
+
+    t.insert(1);
+
+    assert_eq!(t.to_string(), "1");
+    assert_eq!(t.render(Layout::TopDown), "1");
+
+    let mut t = BTree::new(7);
+    for val in 1..7 {
+        t.insert_balanced(val);
+    }
+
+    assert_eq!(
+        t.to_string(),
+        [
+            "4 -> 6 -> 7",
+            "|     `-> 5",
+            "`-> 2 -> 3",
+            "     `-> 1",
+        ].join("\n")
+    );
+
+    assert_eq!(
+        t.render(Layout::TopDown),
+        [
+            "   ___4___",
+            "  /       \\",
+            " _2_     _6_",
+            "/   \\   /   \\",
+            "1   3   5   7",
+        ].join("\n")
+    );
+
+    // Wider values, one-sided children, and more than one `|` waiting at once.
+    let mut t = BTree::new(50);
+    for val in [10, 90, 5, 30, 70, 100, 20, 40, 60, 80] {
+        t.insert(val);
+    }
+
+    assert_eq!(
+        t.to_string(),
+        [
+            "50 -> 90 -> 100",
+            "|      `-> 70 -> 80",
+            "|           `-> 60",
+            "`-> 10 -> 30 -> 40",
+            "     |     `-> 20",
+            "     `-> 5",
+        ].join("\n")
+    );
+
+    assert_eq!(
+        t.render(Layout::TopDown),
+        [
+            "   ___________50__________",
+            "  /                       \\",
+            " _10____             _____90__",
+            "/       \\           /         \\",
+            "5     __30_       __70_      100",
+            "     /     \\     /     \\",
+            "     20    40    60    80",
+        ].join("\n")
+    );
+}
@@ -0,0 +1,139 @@
+use std::fmt::{self, Write};
+
+use super::{BTree, Node};
+
+impl<T: fmt::Display> BTree<T> {
+    /// Export the tree as a [Graphviz](https://graphviz.org) digraph.
+    ///
+    /// Pipe the output through `dot -Tsvg` (or `-Tpng`) to get a picture of the tree.
+    ///
+    /// Graphviz doesn't know anything about left and right, it just lays out edges in the order
+    /// it reads them (with `ordering=out`). So a node with only a right child would have that
+    /// child drawn straight underneath it, looking just like a left child.
+    /// To keep the shape honest we give the missing child an invisible stand-in, which takes up
+    /// the space on the side it would have been on. Leaves don't need stand-ins, there is
+    /// nothing to be lopsided about.
+    ///
+    /// Edges are labelled `L` and `R` too, for good measure.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(2);
+    /// my_tree.insert(3);
+    ///
+    /// assert_eq!(my_tree.to_dot(), r#"digraph BTree {
+    ///     graph [ordering=out];
+    ///     node [shape=circle];
+    ///     n0 [label="2"];
+    ///     null1 [shape=point, style=invis];
+    ///     n0 -> null1 [style=invis];
+    ///     n0 -> n2 [label="R"];
+    ///     n2 [label="3"];
+    /// }
+    /// "#);
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot).expect("writing to a String can't fail");
+        dot
+    }
+
+    ///
+    /// Private API: write_dot
+    ///
+    /// The guts of `to_dot`, written against `fmt::Write` so we can use `?` on every line.
+    ///
+    fn write_dot<W: Write>(&self, dot: &mut W) -> fmt::Result {
+        writeln!(dot, "digraph BTree {{")?;
+        writeln!(dot, "    graph [ordering=out];")?;
+        writeln!(dot, "    node [shape=circle];")?;
+
+        let mut next_id = 0;
+        let mut stack: Vec<(&Node<T>, usize)> = Vec::new();
+
+        if let Some(ref root) = self.root {
+            stack.push((root, next_id));
+            next_id += 1;
+        }
+
+        while let Some((node, id)) = stack.pop() {
+            let label = escape(&node.value.to_string());
+            writeln!(dot, "    n{} [label=\"{}\"];", id, label)?;
+
+            let has_children = node.left.is_some() || node.right.is_some();
+            let mut children = Vec::new();
+
+            for (child, side) in [(&node.left, "L"), (&node.right, "R")] {
+                let child_id = next_id;
+                match child {
+                    Some(child) => {
+                        next_id += 1;
+                        writeln!(dot, "    n{} -> n{} [label=\"{}\"];", id, child_id, side)?;
+                        children.push((&**child, child_id));
+                    },
+                    None if has_children => {
+                        next_id += 1;
+                        writeln!(dot, "    null{} [shape=point, style=invis];", child_id)?;
+                        writeln!(dot, "    n{} -> null{} [style=invis];", id, child_id)?;
+                    },
+                    None => (),
+                }
+            }
+
+            // Push right first so the left sub-tree comes out first, like a pre-order walk.
+            stack.extend(children.into_iter().rev());
+        }
+
+        writeln!(dot, "}}")
+    }
+}
+
+///
+/// Escape a label so it can sit inside a double quoted DOT string.
+///
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[test]
+fn test_to_dot() {
+    let t: BTree<i32> = BTree::empty();
+
+    assert_eq!(
+        t.to_dot(),
+        "digraph BTree {\n    graph [ordering=out];\n    node [shape=circle];\n}\n"
+    );
+
+    let mut t = BTree::new(4);
+    for val in [2, 6, 1, 7] {
+        t.insert(val);
+    }
+
+    assert_eq!(
+        t.to_dot(),
+        [
+            "digraph BTree {",
+            "    graph [ordering=out];",
+            "    node [shape=circle];",
+            "    n0 [label=\"4\"];",
+            "    n0 -> n1 [label=\"L\"];",
+            "    n0 -> n2 [label=\"R\"];",
+            "    n1 [label=\"2\"];",
+            "    n1 -> n3 [label=\"L\"];",
+            "    null4 [shape=point, style=invis];",
+            "    n1 -> null4 [style=invis];",
+            "    n3 [label=\"1\"];",
+            "    n2 [label=\"6\"];",
+            "    null5 [shape=point, style=invis];",
+            "    n2 -> null5 [style=invis];",
+            "    n2 -> n6 [label=\"R\"];",
+            "    n6 [label=\"7\"];",
+            "}",
+            "",
+        ].join("\n")
+    );
+
+    let t = BTree::new("say \"hi\"");
+
+    assert!(t.to_dot().contains(r#"n0 [label="say \"hi\""];"#));
+}
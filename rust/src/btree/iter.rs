@@ -0,0 +1,603 @@
+use std::collections::VecDeque;
+use std::ops::{Bound, RangeBounds};
+
+use super::{size, BTree, Node};
+
+///
+/// Traversals
+///
+/// All of the ways to walk a [BTree].
+///
+/// The textbook way to walk a tree is recursion: visit the left sub-tree, then the node, then
+/// the right sub-tree. That is lovely and short, but Rust iterators are driven one `next` call at
+/// a time so we can't just recurse, and `insert` happily builds trees which are as deep as they
+/// are long, so recursion would blow the stack on those anyway.
+///
+/// Instead each iterator keeps its own explicit stack (or queue) of nodes still to visit.
+///
+impl<T> BTree<T> {
+    /// Iterate over the values in sorted (in-order) order.
+    ///
+    /// The iterator can also be walked from the back, giving the values largest first.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(4);
+    /// for val in [2, 6, 1, 3, 5, 7] {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4, &5, &6, &7]);
+    /// assert_eq!(my_tree.iter().rev().collect::<Vec<_>>(), vec![&7, &6, &5, &4, &3, &2, &1]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            remaining: size(&self.root),
+        };
+        iter.push_left(self.root.as_deref());
+        iter.push_right(self.root.as_deref());
+        iter
+    }
+
+    /// Iterate over the values node first, then the left sub-tree, then the right sub-tree.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(4);
+    /// for val in [2, 6, 1, 3, 5, 7] {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.pre_order().collect::<Vec<_>>(), vec![&4, &2, &1, &3, &6, &5, &7]);
+    /// ```
+    pub fn pre_order(&self) -> PreOrder<'_, T> {
+        PreOrder {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    /// Iterate over the values left sub-tree first, then the right sub-tree, then the node.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(4);
+    /// for val in [2, 6, 1, 3, 5, 7] {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.post_order().collect::<Vec<_>>(), vec![&1, &3, &2, &5, &7, &6, &4]);
+    /// ```
+    pub fn post_order(&self) -> PostOrder<'_, T> {
+        PostOrder {
+            stack: self.root.as_deref().map(|node| (node, false)).into_iter().collect(),
+        }
+    }
+
+    /// Iterate over the values breadth first, one level of the tree at a time.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(4);
+    /// for val in [2, 6, 1, 3, 5, 7] {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.level_order().collect::<Vec<_>>(), vec![&4, &2, &6, &1, &3, &5, &7]);
+    /// ```
+    pub fn level_order(&self) -> LevelOrder<'_, T> {
+        LevelOrder {
+            queue: self.root.as_deref().into_iter().collect(),
+        }
+    }
+}
+
+///
+/// In-order iterator over a [BTree], created by [BTree::iter].
+///
+/// We keep two stacks, one for each end.
+/// The front stack holds the path to the smallest value we haven't visited yet: when we pop a
+/// node off we push the left spine of its right sub-tree, which is where the next smallest value
+/// lives. The back stack is the mirror image.
+///
+/// The two ends don't know about each other, so we count how many values are left and stop when
+/// they meet in the middle.
+///
+pub struct Iter<'a, T> {
+    front: Vec<&'a Node<T>>,
+    back: Vec<&'a Node<T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a Node<T>>) {
+        while let Some(n) = node {
+            self.front.push(n);
+            node = n.left.as_deref();
+        }
+    }
+
+    fn push_right(&mut self, mut node: Option<&'a Node<T>>) {
+        while let Some(n) = node {
+            self.back.push(n);
+            node = n.right.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = self.front.pop()?;
+        self.push_left(node.right.as_deref());
+        self.remaining -= 1;
+
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = self.back.pop()?;
+        self.push_right(node.left.as_deref());
+        self.remaining -= 1;
+
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a BTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+///
+/// Pre-order iterator over a [BTree], created by [BTree::pre_order].
+///
+/// Pop a node, yield it, and push its right then left child so the left child comes off next.
+///
+pub struct PreOrder<'a, T> {
+    stack: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for PreOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+
+        if let Some(ref right) = node.right {
+            self.stack.push(right);
+        }
+        if let Some(ref left) = node.left {
+            self.stack.push(left);
+        }
+
+        Some(&node.value)
+    }
+}
+
+///
+/// Post-order iterator over a [BTree], created by [BTree::post_order].
+///
+/// Each node goes on the stack twice. The first time we see it we put it back (marked as
+/// expanded) underneath its children, the second time we see it its children are done and we
+/// yield it.
+///
+pub struct PostOrder<'a, T> {
+    stack: Vec<(&'a Node<T>, bool)>,
+}
+
+impl<'a, T> Iterator for PostOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+
+            if expanded {
+                return Some(&node.value);
+            }
+
+            self.stack.push((node, true));
+            if let Some(ref right) = node.right {
+                self.stack.push((right, false));
+            }
+            if let Some(ref left) = node.left {
+                self.stack.push((left, false));
+            }
+        }
+    }
+}
+
+///
+/// Level-order (breadth first) iterator over a [BTree], created by [BTree::level_order].
+///
+/// Same as pre-order, but with a queue instead of a stack.
+///
+pub struct LevelOrder<'a, T> {
+    queue: VecDeque<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for LevelOrder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.queue.pop_front()?;
+
+        if let Some(ref left) = node.left {
+            self.queue.push_back(left);
+        }
+        if let Some(ref right) = node.right {
+            self.queue.push_back(right);
+        }
+
+        Some(&node.value)
+    }
+}
+
+impl<T: Ord> BTree<T> {
+    /// Iterate, in sorted order, over just the values which fall inside `range`.
+    ///
+    /// Any kind of range works: `a..b`, `a..=b`, `a..`, `..b`, or a pair of `Bound`s.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::empty();
+    /// for val in [50, 20, 80, 10, 30, 70, 90] {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.range(20..70).collect::<Vec<_>>(), vec![&20, &30, &50]);
+    /// assert_eq!(my_tree.range(25..=70).collect::<Vec<_>>(), vec![&30, &50, &70]);
+    /// assert_eq!(my_tree.range(..20).collect::<Vec<_>>(), vec![&10]);
+    /// assert_eq!(my_tree.range(85..).rev().collect::<Vec<_>>(), vec![&90]);
+    /// assert_eq!(my_tree.range(31..49).next(), None);
+    /// ```
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        let mut iter = Range {
+            front: Vec::new(),
+            back: Vec::new(),
+            finished: false,
+        };
+
+        // Find the path to the smallest value inside the range.
+        // Any node below the start of the range gets skipped along with its whole left sub-tree.
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            if after_start(range.start_bound(), &n.value) {
+                iter.front.push(n);
+                node = n.left.as_deref();
+            } else {
+                node = n.right.as_deref();
+            }
+        }
+
+        // And the path to the largest value inside the range.
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            if before_end(range.end_bound(), &n.value) {
+                iter.back.push(n);
+                node = n.right.as_deref();
+            } else {
+                node = n.left.as_deref();
+            }
+        }
+
+        // If the smallest value past the start is also past the end, the range is empty.
+        iter.finished = match iter.front.last() {
+            Some(first) => !before_end(range.end_bound(), &first.value),
+            None => true,
+        };
+
+        iter
+    }
+}
+
+pub(super) fn after_start<T: Ord>(start: Bound<&T>, val: &T) -> bool {
+    match start {
+        Bound::Included(start) => val >= start,
+        Bound::Excluded(start) => val > start,
+        Bound::Unbounded => true,
+    }
+}
+
+pub(super) fn before_end<T: Ord>(end: Bound<&T>, val: &T) -> bool {
+    match end {
+        Bound::Included(end) => val <= end,
+        Bound::Excluded(end) => val < end,
+        Bound::Unbounded => true,
+    }
+}
+
+///
+/// Iterator over the values of a [BTree] inside a range, created by [BTree::range].
+///
+/// This works the same as [Iter], except the two stacks start at the first and last values in
+/// the range rather than the first and last values in the tree.
+///
+/// Rather than comparing every value against the range as we go, we stop when the two ends run
+/// into each other: the front end yields the node the back end was about to yield, or vice versa.
+///
+pub struct Range<'a, T> {
+    front: Vec<&'a Node<T>>,
+    back: Vec<&'a Node<T>>,
+    finished: bool,
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.finished {
+            return None;
+        }
+
+        let node = self.front.pop()?;
+
+        if self.back.last().is_some_and(|last| std::ptr::eq(node, *last)) {
+            self.finished = true;
+        } else {
+            let mut next = node.right.as_deref();
+            while let Some(n) = next {
+                self.front.push(n);
+                next = n.left.as_deref();
+            }
+        }
+
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Range<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.finished {
+            return None;
+        }
+
+        let node = self.back.pop()?;
+
+        if self.front.last().is_some_and(|first| std::ptr::eq(node, *first)) {
+            self.finished = true;
+        } else {
+            let mut next = node.left.as_deref();
+            while let Some(n) = next {
+                self.back.push(n);
+                next = n.right.as_deref();
+            }
+        }
+
+        Some(&node.value)
+    }
+}
+
+///
+/// Owning in-order iterator over a [BTree].
+///
+/// Works just like [Iter], except we take the left sub-tree out of each node as we push it, so
+/// when a node comes off the stack we can move its value out and keep its right sub-tree.
+///
+/// ```
+/// # use exercises::btree::BTree;
+/// let mut my_tree = BTree::new(String::from("b"));
+/// my_tree.insert(String::from("c"));
+/// my_tree.insert(String::from("a"));
+///
+/// let values: Vec<String> = my_tree.into_iter().collect();
+/// assert_eq!(values, vec!["a", "b", "c"]);
+/// ```
+///
+pub struct IntoIter<T> {
+    stack: Vec<Box<Node<T>>>,
+    remaining: usize,
+}
+
+impl<T> IntoIter<T> {
+    fn push_left(&mut self, mut node: Option<Box<Node<T>>>) {
+        while let Some(mut n) = node {
+            node = n.left.take();
+            self.stack.push(n);
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut node = self.stack.pop()?;
+        self.push_left(node.right.take());
+        self.remaining -= 1;
+
+        Some(node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+///
+/// Whatever is left of the tree still has its right sub-trees attached, so we finish the walk
+/// rather than let them be dropped recursively.
+///
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        for _ in self {}
+    }
+}
+
+impl<T> IntoIterator for BTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> IntoIter<T> {
+        let mut iter = IntoIter {
+            stack: Vec::new(),
+            remaining: size(&self.root),
+        };
+        iter.push_left(self.root.take());
+        iter
+    }
+}
+
+#[test]
+fn test_traversals() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-1000, 1000);
+
+    let mut t = BTree::empty();
+    let mut expected = BTreeSet::new();
+
+    for _ in 0..1024 {
+        let val: i32 = rng.sample(distr);
+        t.insert(val);
+        expected.insert(val);
+    }
+
+    let sorted = expected.iter().collect::<Vec<_>>();
+
+    assert_eq!(t.iter().collect::<Vec<_>>(), sorted);
+    assert_eq!(t.iter().rev().collect::<Vec<_>>(), sorted.iter().rev().cloned().collect::<Vec<_>>());
+    assert_eq!(t.iter().len(), sorted.len());
+
+    // Walking from both ends at once should meet in the middle without repeating anything.
+    let mut iter = t.iter();
+    let mut both = Vec::new();
+    while let Some(front) = iter.next() {
+        both.push(front);
+        if let Some(back) = iter.next_back() {
+            both.push(back);
+        }
+    }
+    both.sort();
+    assert_eq!(both, sorted);
+
+    // Every traversal visits every value exactly once.
+    for traversal in [
+        t.pre_order().collect::<Vec<_>>(),
+        t.post_order().collect::<Vec<_>>(),
+        t.level_order().collect::<Vec<_>>(),
+    ] {
+        let mut traversal = traversal;
+        traversal.sort();
+        assert_eq!(traversal, sorted);
+    }
+
+    // The first value pre-order and the last value post-order are both the root.
+    let root = t.root.as_ref().map(|node| &node.value);
+    assert_eq!(t.pre_order().next(), root);
+    assert_eq!(t.post_order().last(), root);
+    assert_eq!(t.level_order().next(), root);
+
+    assert_eq!(t.into_iter().collect::<Vec<_>>(), expected.into_iter().collect::<Vec<_>>());
+
+    let empty: BTree<i32> = BTree::empty();
+    assert_eq!(empty.iter().next(), None);
+    assert_eq!(empty.pre_order().next(), None);
+    assert_eq!(empty.post_order().next(), None);
+    assert_eq!(empty.level_order().next(), None);
+    assert_eq!(empty.into_iter().next(), None);
+}
+
+#[test]
+fn test_range() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(0, 100);
+
+    let mut t = BTree::empty();
+    let mut expected = BTreeSet::new();
+
+    for _ in 0..50 {
+        let val: i32 = rng.sample(distr);
+        t.insert(val);
+        expected.insert(val);
+    }
+
+    let bound = |val: i32, kind: u8| match kind {
+        0 => Bound::Included(val),
+        1 => Bound::Excluded(val),
+        _ => Bound::Unbounded,
+    };
+
+    for _ in 0..1024 {
+        let start = bound(rng.sample(distr), rng.gen_range(0..3));
+        let end = bound(rng.sample(distr), rng.gen_range(0..3));
+
+        // BTreeSet panics on backwards ranges, we just return nothing.
+        let backwards = match (start, end) {
+            (Bound::Included(a), Bound::Included(b)) => a > b,
+            (Bound::Included(a), Bound::Excluded(b))
+            | (Bound::Excluded(a), Bound::Included(b))
+            | (Bound::Excluded(a), Bound::Excluded(b)) => a >= b,
+            _ => false,
+        };
+        if backwards {
+            assert_eq!(t.range((start, end)).next(), None);
+            continue;
+        }
+
+        assert!(t.range((start, end)).eq(expected.range((start, end))));
+        assert!(t.range((start, end)).rev().eq(expected.range((start, end)).rev()));
+
+        // Alternate ends, the two halves should still cover the range exactly once.
+        let mut iter = t.range((start, end));
+        let mut expected_iter = expected.range((start, end));
+        loop {
+            let front = iter.next();
+            assert_eq!(front, expected_iter.next());
+            let back = iter.next_back();
+            assert_eq!(back, expected_iter.next_back());
+            if front.is_none() && back.is_none() {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_traversals_degenerate() {
+    // Sorted input makes `insert` build one long vine, which would be too deep to recurse over.
+    let mut t = BTree::empty();
+    for val in 0..10_000 {
+        t.insert(val);
+    }
+
+    assert!(t.iter().copied().eq(0..10_000));
+    assert!(t.iter().rev().copied().eq((0..10_000).rev()));
+    assert!(t.pre_order().copied().eq(0..10_000));
+    assert!(t.post_order().copied().eq((0..10_000).rev()));
+    assert!(t.level_order().copied().eq(0..10_000));
+    assert!(t.range(500..9_500).copied().eq(500..9_500));
+    assert!(t.into_iter().eq(0..10_000));
+}
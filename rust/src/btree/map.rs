@@ -0,0 +1,257 @@
+use std::cmp::Ordering;
+
+use super::BTree;
+
+///
+/// TreeMap
+///
+/// An ordered key/value map built out of the same nodes as [BTree].
+///
+/// Each node in the tree holds a key and a value, but the tree only ever looks at the key when
+/// deciding where a node goes. That lets us re-use all of the `BTree` machinery (inserting,
+/// rotating, removing) as long as the thing we store compares by key alone.
+///
+/// Unlike `BTree::insert`, which drops duplicates on the floor, `TreeMap::insert` replaces the
+/// value for an existing key and hands the old one back.
+///
+/// The map always inserts with `insert_balanced` and removes with rebalancing, so lookups are
+/// O(log n) no matter what order the keys show up in.
+///
+/// ```
+/// # use exercises::btree::TreeMap;
+/// let mut m = TreeMap::new();
+///
+/// assert_eq!(m.insert(3, "three"), None);
+/// assert_eq!(m.insert(1, "one"), None);
+/// assert_eq!(m.insert(3, "THREE"), Some("three"));
+///
+/// assert_eq!(m.get(&3), Some(&"THREE"));
+/// assert_eq!(m.get(&2), None);
+///
+/// if let Some(v) = m.get_mut(&1) {
+///     *v = "uno";
+/// }
+/// assert_eq!(m.get(&1), Some(&"uno"));
+///
+/// assert_eq!(m.remove(&1), Some("uno"));
+/// assert_eq!(m.remove(&1), None);
+/// assert_eq!(m.len(), 1);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct TreeMap<K, V> {
+    tree: BTree<KeyValue<K, V>>,
+}
+
+///
+/// A key and its value, compared by key only.
+///
+#[derive(Debug, Clone)]
+struct KeyValue<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K: Ord, V> PartialEq for KeyValue<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Ord, V> Eq for KeyValue<K, V> {}
+
+impl<K: Ord, V> PartialOrd for KeyValue<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for KeyValue<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<K: Ord, V> Default for TreeMap<K, V> {
+    fn default() -> Self {
+        TreeMap::new()
+    }
+}
+
+impl<K: Ord, V> TreeMap<K, V> {
+    pub fn new() -> TreeMap<K, V> {
+        TreeMap {
+            tree: BTree::empty(),
+        }
+    }
+
+    /// Number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Insert a value for `key`, returning the value it replaced (if any).
+    ///
+    /// ```
+    /// # use exercises::btree::TreeMap;
+    /// let mut m = TreeMap::new();
+    /// assert_eq!(m.insert("id-1", 10), None);
+    /// assert_eq!(m.insert("id-1", 11), Some(10));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+
+        self.tree.insert_balanced(KeyValue { key, value });
+
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.tree
+            .find_by(|pair| key.cmp(&pair.key))
+            .map(|pair| &pair.value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tree
+            .find_by_mut(|pair| key.cmp(&pair.key))
+            .map(|pair| &mut pair.value)
+    }
+
+    /// Get an [Entry] for `key`, for updating a value in place or inserting it if it is missing.
+    ///
+    /// ```
+    /// # use exercises::btree::TreeMap;
+    /// let mut counts = TreeMap::new();
+    ///
+    /// for word in "the cat and the hat".split(' ') {
+    ///     *counts.entry(word).or_insert(0) += 1;
+    /// }
+    ///
+    /// assert_eq!(counts.get(&"the"), Some(&2));
+    /// assert_eq!(counts.get(&"cat"), Some(&1));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        Entry { map: self, key }
+    }
+
+    /// Remove `key` from the map, returning its value if it was there.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.tree
+            .remove_by(&|pair: &KeyValue<K, V>| key.cmp(&pair.key), true)
+            .map(|pair| pair.value)
+    }
+}
+
+///
+/// Entry
+///
+/// A spot in a [TreeMap] for a key which may or may not have a value yet.
+///
+/// This is a much simpler take on the standard library's `Entry` enum.
+/// We don't hang on to the node we found, we just look the key up again when we need it.
+/// That costs an extra O(log n) walk, but keeps us from fighting the borrow checker over a
+/// reference into a tree which might get rotated out from under us.
+///
+/// Inserting needs a copy of the key because the key we own gets moved into the tree, and we
+/// still need one to find the new node afterwards.
+///
+pub struct Entry<'a, K, V> {
+    map: &'a mut TreeMap<K, V>,
+    key: K,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Run `f` on the value if the key is already in the map.
+    ///
+    /// ```
+    /// # use exercises::btree::TreeMap;
+    /// let mut m = TreeMap::new();
+    /// m.insert(1, 10);
+    ///
+    /// m.entry(1).and_modify(|v| *v += 1).or_insert(0);
+    /// m.entry(2).and_modify(|v| *v += 1).or_insert(0);
+    ///
+    /// assert_eq!(m.get(&1), Some(&11));
+    /// assert_eq!(m.get(&2), Some(&0));
+    /// ```
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        if let Some(value) = self.map.get_mut(&self.key) {
+            f(value);
+        }
+        self
+    }
+}
+
+impl<'a, K: Ord + Clone, V> Entry<'a, K, V> {
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        let Entry { map, key } = self;
+
+        if map.get(&key).is_none() {
+            map.insert(key.clone(), default());
+        }
+
+        map.get_mut(&key).expect("key was just inserted")
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+}
+
+#[test]
+fn test_tree_map() {
+    use rand::prelude::*;
+    use std::collections::BTreeMap;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(1, 100);
+
+    let mut m = TreeMap::new();
+    let mut expected = BTreeMap::new();
+
+    for i in 0..2048 {
+        let key: u8 = rng.sample(distr);
+
+        if rng.gen_bool(0.3) {
+            assert_eq!(m.remove(&key), expected.remove(&key));
+        } else {
+            assert_eq!(m.insert(key, i), expected.insert(key, i));
+        }
+
+        assert_eq!(m.len(), expected.len());
+
+        assert!(m.tree.balanced());
+    }
+
+    for key in 0..=u8::MAX {
+        assert_eq!(m.get(&key), expected.get(&key));
+    }
+
+    for key in 0..=u8::MAX {
+        *m.entry(key).or_default() += 1;
+        *expected.entry(key).or_default() += 1;
+    }
+
+    for key in 0..=u8::MAX {
+        assert_eq!(m.get(&key), expected.get(&key));
+    }
+}
@@ -0,0 +1,328 @@
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+
+use super::{BTree, Iter};
+
+///
+/// TreeMultiset
+///
+/// A sorted bag of values, where the same value can go in more than once.
+///
+/// `BTree` drops duplicates on the floor. Storing every copy as its own node would work, but
+/// then a value seen a million times costs a million nodes, and the duplicates all have to go
+/// somewhere in the ordering. So instead, like [TreeMap](super::TreeMap), each node holds a value
+/// and a count, and the tree only looks at the value when deciding where the node goes.
+/// Inserting a value we already have just bumps its count.
+///
+/// That makes it a histogram: `counts` gives you each distinct value with how many times it was
+/// seen, in order.
+///
+/// ```
+/// # use exercises::btree::TreeMultiset;
+/// let readings = [3, 1, 3, 2, 3, 1];
+/// let mut bag: TreeMultiset<i32> = readings.iter().copied().collect();
+///
+/// assert_eq!(bag.len(), 6);
+/// assert_eq!(bag.count(&3), 3);
+/// assert_eq!(bag.counts().collect::<Vec<_>>(), vec![(&1, 2), (&2, 1), (&3, 3)]);
+/// assert_eq!(bag.iter().collect::<Vec<_>>(), vec![&1, &1, &2, &3, &3, &3]);
+///
+/// assert_eq!(bag.remove_one(&3), true);
+/// assert_eq!(bag.count(&3), 2);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct TreeMultiset<T> {
+    tree: BTree<Counted<T>>,
+    len: usize,
+}
+
+///
+/// A value and how many times it is in the multiset, compared by value only.
+///
+#[derive(Debug, Clone)]
+struct Counted<T> {
+    value: T,
+    count: usize,
+}
+
+impl<T: Ord> PartialEq for Counted<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Ord> Eq for Counted<T> {}
+
+impl<T: Ord> PartialOrd for Counted<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Counted<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: Ord> Default for TreeMultiset<T> {
+    fn default() -> Self {
+        TreeMultiset::new()
+    }
+}
+
+impl<T: Ord> TreeMultiset<T> {
+    pub fn new() -> TreeMultiset<T> {
+        TreeMultiset {
+            tree: BTree::empty(),
+            len: 0,
+        }
+    }
+
+    /// Number of values in the multiset, counting every copy.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of different values in the multiset, which is how many nodes the tree has.
+    ///
+    /// ```
+    /// # use exercises::btree::TreeMultiset;
+    /// let bag: TreeMultiset<char> = "hello".chars().collect();
+    ///
+    /// assert_eq!(bag.len(), 5);
+    /// assert_eq!(bag.distinct_len(), 4);
+    /// ```
+    pub fn distinct_len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// How many times `val` is in the multiset, 0 if it isn't there at all.
+    pub fn count(&self, val: &T) -> usize {
+        self.tree
+            .find_by(|counted| val.cmp(&counted.value))
+            .map_or(0, |counted| counted.count)
+    }
+
+    pub fn contains(&self, val: &T) -> bool {
+        self.count(val) > 0
+    }
+
+    /// Add one more `val`.
+    ///
+    /// If it's already there we bump its count, otherwise it gets a node of its own with a count
+    /// of 1. Nodes are inserted with `insert_balanced`, so the tree stays O(log n) deep whatever
+    /// order the values show up in.
+    ///
+    /// ```
+    /// # use exercises::btree::TreeMultiset;
+    /// let mut bag = TreeMultiset::new();
+    /// bag.insert("a");
+    /// bag.insert("a");
+    ///
+    /// assert_eq!(bag.count(&"a"), 2);
+    /// ```
+    pub fn insert(&mut self, val: T) {
+        self.len += 1;
+
+        if let Some(counted) = self.tree.find_by_mut(|counted| val.cmp(&counted.value)) {
+            counted.count += 1;
+            return;
+        }
+
+        self.tree.insert_balanced(Counted { value: val, count: 1 });
+    }
+
+    /// Take away one `val`, returning whether there was one to take.
+    ///
+    /// When the count gets down to 0 the node is removed from the tree altogether.
+    ///
+    /// ```
+    /// # use exercises::btree::TreeMultiset;
+    /// let mut bag: TreeMultiset<i32> = vec![7, 7].into_iter().collect();
+    ///
+    /// assert_eq!(bag.remove_one(&7), true);
+    /// assert_eq!(bag.remove_one(&7), true);
+    /// assert_eq!(bag.remove_one(&7), false);
+    /// assert_eq!(bag.is_empty(), true);
+    /// ```
+    pub fn remove_one(&mut self, val: &T) -> bool {
+        let counted = match self.tree.find_by_mut(|counted| val.cmp(&counted.value)) {
+            Some(counted) => counted,
+            None => return false,
+        };
+
+        counted.count -= 1;
+        if counted.count == 0 {
+            self.tree.remove_by(&|counted: &Counted<T>| val.cmp(&counted.value), true);
+        }
+
+        self.len -= 1;
+        true
+    }
+
+    /// Take away every `val`, returning how many there were.
+    pub fn remove_all(&mut self, val: &T) -> usize {
+        let removed = self
+            .tree
+            .remove_by(&|counted: &Counted<T>| val.cmp(&counted.value), true)
+            .map_or(0, |counted| counted.count);
+
+        self.len -= removed;
+        removed
+    }
+}
+
+impl<T> TreeMultiset<T> {
+    /// Iterate over the values in sorted order, each one as many times as it was inserted.
+    pub fn iter(&self) -> MultisetIter<'_, T> {
+        MultisetIter {
+            inner: self.tree.iter(),
+            current: None,
+        }
+    }
+
+    /// Iterate over each distinct value in sorted order, along with its count.
+    pub fn counts(&self) -> Counts<'_, T> {
+        Counts {
+            inner: self.tree.iter(),
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for TreeMultiset<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> TreeMultiset<T> {
+        let mut multiset = TreeMultiset::new();
+        multiset.extend(iter);
+        multiset
+    }
+}
+
+impl<T: Ord> Extend<T> for TreeMultiset<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.insert(val);
+        }
+    }
+}
+
+///
+/// Iterator over a [TreeMultiset] which hands out each value once per copy.
+///
+/// We walk the tree in order as usual, and hang on to the current node until we've handed its
+/// value out `count` times.
+///
+pub struct MultisetIter<'a, T> {
+    inner: Iter<'a, Counted<T>>,
+    current: Option<(&'a T, usize)>,
+}
+
+impl<'a, T> Iterator for MultisetIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some((value, ref mut remaining)) = self.current {
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    return Some(value);
+                }
+            }
+
+            let counted = self.inner.next()?;
+            self.current = Some((&counted.value, counted.count));
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a TreeMultiset<T> {
+    type Item = &'a T;
+    type IntoIter = MultisetIter<'a, T>;
+
+    fn into_iter(self) -> MultisetIter<'a, T> {
+        self.iter()
+    }
+}
+
+///
+/// Iterator over the distinct values in a [TreeMultiset] and their counts.
+///
+pub struct Counts<'a, T> {
+    inner: Iter<'a, Counted<T>>,
+}
+
+impl<'a, T> Iterator for Counts<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<(&'a T, usize)> {
+        self.inner.next().map(|counted| (&counted.value, counted.count))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Counts<'a, T> {}
+
+#[test]
+fn test_tree_multiset() {
+    use rand::prelude::*;
+    use std::collections::BTreeMap;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(1, 100);
+
+    let mut bag = TreeMultiset::new();
+    let mut expected: BTreeMap<u8, usize> = BTreeMap::new();
+
+    for _ in 0..4096 {
+        let val: u8 = rng.sample(distr);
+
+        match rng.gen_range(0..10) {
+            0..=5 => {
+                bag.insert(val);
+                *expected.entry(val).or_default() += 1;
+            },
+            6..=8 => {
+                let was_there = expected.get(&val).is_some_and(|&count| count > 0);
+                assert_eq!(bag.remove_one(&val), was_there);
+                if was_there {
+                    *expected.get_mut(&val).unwrap() -= 1;
+                }
+            },
+            _ => {
+                let count = expected.remove(&val).unwrap_or(0);
+                assert_eq!(bag.remove_all(&val), count);
+            },
+        }
+        expected.retain(|_, count| *count > 0);
+
+        assert_eq!(bag.len(), expected.values().sum::<usize>());
+        assert_eq!(bag.distinct_len(), expected.len());
+        assert!(bag.tree.is_height_balanced());
+    }
+
+    for val in 0..=u8::MAX {
+        assert_eq!(bag.count(&val), expected.get(&val).copied().unwrap_or(0));
+    }
+
+    assert!(bag.counts().map(|(&val, count)| (val, count)).eq(expected.clone()));
+
+    let flat = expected
+        .iter()
+        .flat_map(|(&val, &count)| std::iter::repeat_n(val, count))
+        .collect::<Vec<_>>();
+    assert_eq!(bag.iter().copied().collect::<Vec<_>>(), flat);
+    assert_eq!(bag.iter().count(), bag.len());
+
+    let empty: TreeMultiset<u8> = TreeMultiset::new();
+    assert_eq!(empty.iter().next(), None);
+    assert_eq!(empty.count(&1), 0);
+}
@@ -0,0 +1,602 @@
+use std::cmp::Ordering;
+
+///
+/// MultiwayBTree
+///
+/// Despite the name, [BTree](super::BTree) is a binary search tree. This is the real thing: a
+/// B-tree, where every node holds a whole sorted array of keys and has one more child than it has
+/// keys. The keys in a node split up the range of values between its children, so the node
+/// `[10, 20]` has one child for everything below 10, one for 10..20, and one for above 20.
+///
+/// How many keys a node can hold is set by the tree's "minimum degree" `t`:
+/// * Every node apart from the root holds between `t - 1` and `2t - 1` keys.
+/// * The root holds between 1 and `2t - 1` keys (or none, if the tree is empty).
+/// * Every leaf is at the same depth.
+///
+/// A node is meant to fill a disk page or a cache line, so one visit to a node rules out all but
+/// one of up to `2t` sub-trees at once, and the tree is only `log_t(n)` levels deep.
+///
+/// Instead of rotating, the tree stays balanced by splitting and merging nodes:
+/// * Inserting never goes into a full node. On the way down every full node we pass is split in
+///   two around its middle key, which moves up into the parent (which can't be full, we already
+///   split it if it was). Splitting the root is the only way the tree grows taller.
+/// * Removing never takes a key out of a node which is at the minimum. On the way down every such
+///   node gets a key from a sibling which can spare one, or else is merged with a sibling and the
+///   key between them in the parent. Merging the root's last two children is the only way the tree
+///   gets shorter.
+///
+/// Both work in one pass from the root down, no need to come back up.
+///
+/// ```
+/// # use exercises::btree::MultiwayBTree;
+/// let mut t = MultiwayBTree::with_min_degree(2);
+/// for val in 1..=10 {
+///     t.insert(val);
+/// }
+///
+/// assert_eq!(t.len(), 10);
+/// assert_eq!(t.depth(), 3);
+/// assert_eq!(t.balanced(), true);
+///
+/// assert_eq!(t.remove(&5), true);
+/// assert_eq!(t.contains(&5), false);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct MultiwayBTree<T> {
+    root: Option<Box<MultiwayNode<T>>>,
+    min_degree: usize,
+    len: usize,
+}
+
+///
+/// A single node in a [MultiwayBTree].
+///
+/// A leaf has no children, anything else has exactly `keys.len() + 1`.
+///
+#[derive(Debug, Clone)]
+struct MultiwayNode<T> {
+    keys: Vec<T>,
+    children: Vec<MultiwayNode<T>>,
+}
+
+/// The minimum degree `empty` and `new` use, the same one the standard library's `BTreeMap`
+/// settled on: nodes of 5 to 11 keys.
+const DEFAULT_MIN_DEGREE: usize = 6;
+
+impl<T: Ord> Default for MultiwayBTree<T> {
+    fn default() -> Self {
+        MultiwayBTree::empty()
+    }
+}
+
+impl<T: Ord> MultiwayBTree<T> {
+    /// A tree holding just `val`, with the default minimum degree.
+    pub fn new(val: T) -> MultiwayBTree<T> {
+        let mut tree = MultiwayBTree::empty();
+        tree.insert(val);
+        tree
+    }
+
+    /// An empty tree with the default minimum degree, 6.
+    ///
+    /// ```
+    /// # use exercises::btree::MultiwayBTree;
+    /// let t: MultiwayBTree<i32> = MultiwayBTree::empty();
+    ///
+    /// assert_eq!(t.min_degree(), 6);
+    /// assert_eq!(t.is_empty(), true);
+    /// ```
+    pub fn empty() -> MultiwayBTree<T> {
+        MultiwayBTree::with_min_degree(DEFAULT_MIN_DEGREE)
+    }
+
+    /// An empty tree where every node (other than the root) holds between `min_degree - 1` and
+    /// `2 * min_degree - 1` keys.
+    ///
+    /// Panics if `min_degree` is less than 2, the smallest tree that still works (a 2-3-4 tree).
+    /// With a minimum degree of 1 a node could be left with no keys at all, and splitting a full
+    /// node of one key would leave nothing to move up.
+    ///
+    /// ```should_panic
+    /// # use exercises::btree::MultiwayBTree;
+    /// let t: MultiwayBTree<i32> = MultiwayBTree::with_min_degree(1);
+    /// ```
+    pub fn with_min_degree(min_degree: usize) -> MultiwayBTree<T> {
+        assert!(min_degree >= 2, "a B-tree needs a minimum degree of at least 2");
+
+        MultiwayBTree {
+            root: None,
+            min_degree,
+            len: 0,
+        }
+    }
+
+    pub fn min_degree(&self) -> usize {
+        self.min_degree
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// The number of levels in the tree, or 0 for an empty tree.
+    ///
+    /// Every leaf is at the same depth, so we only need to walk down the left edge.
+    ///
+    /// ```
+    /// # use exercises::btree::MultiwayBTree;
+    /// let mut t = MultiwayBTree::with_min_degree(3);
+    ///
+    /// assert_eq!(t.depth(), 0);
+    ///
+    /// // A node holds up to 5 keys, so the 6th splits the root.
+    /// for val in 1..=5 {
+    ///     t.insert(val);
+    /// }
+    /// assert_eq!(t.depth(), 1);
+    ///
+    /// t.insert(6);
+    /// assert_eq!(t.depth(), 2);
+    /// ```
+    pub fn depth(&self) -> isize {
+        let mut depth = 0;
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            depth += 1;
+            node = n.children.first();
+        }
+        depth
+    }
+
+    /// Is the tree a proper B-tree?
+    ///
+    /// For a binary tree "balanced" means the two sides are about the same depth. A B-tree is
+    /// stricter than that, *every* leaf is at exactly the same depth, and the key counts stay
+    /// inside the bounds the minimum degree sets. `insert` and `remove` keep it that way, so
+    /// this should always be true. It walks the whole tree to check.
+    ///
+    /// ```
+    /// # use exercises::btree::MultiwayBTree;
+    /// let mut t = MultiwayBTree::with_min_degree(2);
+    /// for val in (0..100).rev() {
+    ///     t.insert(val);
+    ///     assert_eq!(t.balanced(), true);
+    /// }
+    /// ```
+    pub fn balanced(&self) -> bool {
+        let root = match self.root {
+            Some(ref root) => root,
+            None => return true,
+        };
+
+        let max_keys = 2 * self.min_degree - 1;
+        let depth = self.depth();
+
+        let mut stack = vec![(&**root, 1)];
+        while let Some((node, level)) = stack.pop() {
+            let min_keys = if level == 1 { 1 } else { self.min_degree - 1 };
+            if node.keys.len() < min_keys || node.keys.len() > max_keys {
+                return false;
+            }
+
+            if node.is_leaf() {
+                if level != depth {
+                    return false;
+                }
+            } else if node.children.len() != node.keys.len() + 1 {
+                return false;
+            }
+
+            stack.extend(node.children.iter().map(|child| (child, level + 1)));
+        }
+
+        true
+    }
+
+    /// Is `val` in the tree?
+    ///
+    /// A binary search in each node tells us either that `val` is there, or which child to go down
+    /// into next.
+    pub fn contains(&self, val: &T) -> bool {
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            match n.keys.binary_search(val) {
+                Ok(_) => return true,
+                Err(i) => node = n.children.get(i),
+            }
+        }
+        false
+    }
+
+    /// Insert a value into the tree.
+    ///
+    /// Duplicate values are not stored.
+    ///
+    /// ```
+    /// # use exercises::btree::MultiwayBTree;
+    /// let mut t = MultiwayBTree::with_min_degree(2);
+    /// t.insert(1);
+    /// t.insert(1);
+    ///
+    /// assert_eq!(t.len(), 1);
+    /// ```
+    pub fn insert(&mut self, val: T) {
+        let t = self.min_degree;
+
+        let root = self.root.get_or_insert_with(|| Box::new(MultiwayNode::leaf()));
+        if root.is_full(t) {
+            let old = std::mem::replace(root, Box::new(MultiwayNode::leaf()));
+            root.children.push(*old);
+            root.split_child(0, t);
+        }
+
+        let mut node = &mut **root;
+        loop {
+            let mut i = match node.keys.binary_search(&val) {
+                Ok(_) => return,
+                Err(i) => i,
+            };
+
+            if node.is_leaf() {
+                node.keys.insert(i, val);
+                self.len += 1;
+                return;
+            }
+
+            if node.children[i].is_full(t) {
+                node.split_child(i, t);
+                // The middle key of the child we split is now at `i`, which side are we on?
+                match val.cmp(&node.keys[i]) {
+                    Ordering::Equal => return,
+                    Ordering::Greater => i += 1,
+                    Ordering::Less => (),
+                }
+            }
+
+            node = &mut node.children[i];
+        }
+    }
+
+    /// Remove a value from the tree, returning whether it was there to remove.
+    ///
+    /// If the value is in a leaf it can just be taken out. If it's in an inner node it has to be
+    /// replaced, by the biggest value in the child before it or the smallest in the child after
+    /// it, the same way `BTree::remove` uses the in-order successor.
+    ///
+    /// ```
+    /// # use exercises::btree::MultiwayBTree;
+    /// let mut t = MultiwayBTree::with_min_degree(2);
+    /// for val in 1..=20 {
+    ///     t.insert(val);
+    /// }
+    /// for val in 1..=19 {
+    ///     assert_eq!(t.remove(&val), true);
+    /// }
+    ///
+    /// assert_eq!(t.remove(&1), false);
+    /// assert_eq!(t.len(), 1);
+    /// assert_eq!(t.depth(), 1);
+    /// ```
+    pub fn remove(&mut self, val: &T) -> bool {
+        let t = self.min_degree;
+
+        let root = match self.root {
+            Some(ref mut root) => root,
+            None => return false,
+        };
+
+        let removed = root.remove(val, t).is_some();
+
+        // The root may have run out of keys, either by losing its last one or by merging its
+        // last two children together. Its one child (if any) takes over.
+        if root.keys.is_empty() {
+            self.root = root.children.pop().map(Box::new);
+        }
+
+        if removed {
+            self.len -= 1;
+        }
+        removed
+    }
+}
+
+impl<T> MultiwayBTree<T> {
+    /// Iterate over the values in sorted order.
+    ///
+    /// ```
+    /// # use exercises::btree::MultiwayBTree;
+    /// let mut t = MultiwayBTree::with_min_degree(2);
+    /// for val in [5, 1, 4, 2, 3] {
+    ///     t.insert(val);
+    /// }
+    ///
+    /// assert_eq!(t.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4, &5]);
+    /// ```
+    pub fn iter(&self) -> MultiwayIter<'_, T> {
+        let mut iter = MultiwayIter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+}
+
+impl<T: Ord> MultiwayNode<T> {
+    fn leaf() -> MultiwayNode<T> {
+        MultiwayNode {
+            keys: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    fn is_full(&self, t: usize) -> bool {
+        self.keys.len() == 2 * t - 1
+    }
+
+    ///
+    /// Private API: split_child
+    ///
+    /// Splits the full child at `i` in two around its middle key, which moves up into this node.
+    ///
+    /// ```text
+    ///        [ 10 ]                    [ 10  30 ]
+    ///       /      \                  /    |     \
+    ///     ..   [20 30 40]    =>     ..   [20]    [40]
+    /// ```
+    ///
+    fn split_child(&mut self, i: usize, t: usize) {
+        let child = &mut self.children[i];
+
+        let right = MultiwayNode {
+            keys: child.keys.split_off(t),
+            children: if child.is_leaf() { Vec::new() } else { child.children.split_off(t) },
+        };
+        let middle = child.keys.pop().expect("a full node has a middle key");
+
+        self.keys.insert(i, middle);
+        self.children.insert(i + 1, right);
+    }
+
+    ///
+    /// Private API: remove
+    ///
+    /// Removes `val` from this sub-tree. This node must have at least `t` keys (or be the root),
+    /// so it can afford to lose one.
+    ///
+    fn remove(&mut self, val: &T, t: usize) -> Option<T> {
+        match self.keys.binary_search(val) {
+            Ok(i) if self.is_leaf() => Some(self.keys.remove(i)),
+            Err(_) if self.is_leaf() => None,
+            Ok(i) => {
+                if self.children[i].keys.len() >= t {
+                    let predecessor = self.children[i].remove_max(t);
+                    Some(std::mem::replace(&mut self.keys[i], predecessor))
+                } else if self.children[i + 1].keys.len() >= t {
+                    let successor = self.children[i + 1].remove_min(t);
+                    Some(std::mem::replace(&mut self.keys[i], successor))
+                } else {
+                    // Neither side can spare a key, so `val` moves down into the merged child.
+                    self.merge(i);
+                    self.children[i].remove(val, t)
+                }
+            },
+            Err(i) => {
+                let i = self.fill(i, t);
+                self.children[i].remove(val, t)
+            },
+        }
+    }
+
+    ///
+    /// Private API: remove_max
+    ///
+    /// Removes the biggest value in this sub-tree, which is the last key of the right-most leaf.
+    ///
+    fn remove_max(&mut self, t: usize) -> T {
+        if self.is_leaf() {
+            return self.keys.pop().expect("nodes we remove from always have a key to spare");
+        }
+        let i = self.fill(self.children.len() - 1, t);
+        self.children[i].remove_max(t)
+    }
+
+    ///
+    /// Private API: remove_min
+    ///
+    /// Removes the smallest value in this sub-tree, the first key of the left-most leaf.
+    ///
+    fn remove_min(&mut self, t: usize) -> T {
+        if self.is_leaf() {
+            return self.keys.remove(0);
+        }
+        let i = self.fill(0, t);
+        self.children[i].remove_min(t)
+    }
+
+    ///
+    /// Private API: fill
+    ///
+    /// Makes sure the child at `i` has at least `t` keys before we go down into it, returning where
+    /// that child is afterwards (a merge with the sibling before it moves it).
+    ///
+    /// * If the sibling before it has a key to spare, that sibling's last key moves up into this
+    ///   node, and the key between them moves down into the child. The sibling's last child moves
+    ///   across with it.
+    /// * Or the same with the sibling after it.
+    /// * Otherwise both siblings are at the minimum, so the child is merged with one of them.
+    ///
+    fn fill(&mut self, i: usize, t: usize) -> usize {
+        if self.children[i].keys.len() >= t {
+            return i;
+        }
+
+        if i > 0 && self.children[i - 1].keys.len() >= t {
+            let (before, after) = self.children.split_at_mut(i);
+            let (left, child) = (&mut before[i - 1], &mut after[0]);
+
+            let up = left.keys.pop().expect("a sibling with a key to spare");
+            child.keys.insert(0, std::mem::replace(&mut self.keys[i - 1], up));
+            if let Some(moved) = left.children.pop() {
+                child.children.insert(0, moved);
+            }
+            i
+        } else if i + 1 < self.children.len() && self.children[i + 1].keys.len() >= t {
+            let (before, after) = self.children.split_at_mut(i + 1);
+            let (child, right) = (&mut before[i], &mut after[0]);
+
+            let up = right.keys.remove(0);
+            child.keys.push(std::mem::replace(&mut self.keys[i], up));
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+            i
+        } else if i + 1 < self.children.len() {
+            self.merge(i);
+            i
+        } else {
+            self.merge(i - 1);
+            i - 1
+        }
+    }
+
+    ///
+    /// Private API: merge
+    ///
+    /// Merges the children either side of key `i`, and the key itself, into one node.
+    /// Both children must be at the minimum, `t - 1` keys, so the result has `2t - 1`.
+    ///
+    fn merge(&mut self, i: usize) {
+        let right = self.children.remove(i + 1);
+        let middle = self.keys.remove(i);
+
+        let left = &mut self.children[i];
+        left.keys.push(middle);
+        left.keys.extend(right.keys);
+        left.children.extend(right.children);
+    }
+}
+
+///
+/// In-order iterator over a [MultiwayBTree].
+///
+/// Each entry on the stack is a node and the index of its next key. Before handing out key `i` we
+/// have to have visited all of child `i`, so we push the left edge of a sub-tree just like
+/// [Iter](super::Iter) does, and after handing out key `i` we push the left edge of child `i + 1`.
+///
+pub struct MultiwayIter<'a, T> {
+    stack: Vec<(&'a MultiwayNode<T>, usize)>,
+}
+
+impl<'a, T> MultiwayIter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a MultiwayNode<T>>) {
+        while let Some(n) = node {
+            self.stack.push((n, 0));
+            node = n.children.first();
+        }
+    }
+}
+
+impl<'a, T> Iterator for MultiwayIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let (node, i) = self.stack.pop()?;
+            if i < node.keys.len() {
+                self.stack.push((node, i + 1));
+                self.push_left(node.children.get(i + 1));
+                return Some(&node.keys[i]);
+            }
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a MultiwayBTree<T> {
+    type Item = &'a T;
+    type IntoIter = MultiwayIter<'a, T>;
+
+    fn into_iter(self) -> MultiwayIter<'a, T> {
+        self.iter()
+    }
+}
+
+#[test]
+fn test_multiway_btree() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    // The invariants `balanced` doesn't cover: keys sorted within each node, and between the keys
+    // of their parents.
+    fn check<T: Ord>(tree: &MultiwayBTree<T>) {
+        assert!(tree.balanced());
+        assert!(tree.iter().zip(tree.iter().skip(1)).all(|(a, b)| a < b));
+        assert_eq!(tree.iter().count(), tree.len());
+    }
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-500, 500);
+
+    for t in 2..=5 {
+        let mut tree = MultiwayBTree::with_min_degree(t);
+        let mut oracle = BTreeSet::new();
+
+        for _ in 0..2048 {
+            let val = rng.sample(distr);
+            if rng.gen_bool(0.6) {
+                tree.insert(val);
+                oracle.insert(val);
+            } else {
+                assert_eq!(tree.remove(&val), oracle.remove(&val));
+            }
+            check(&tree);
+        }
+
+        assert!(tree.iter().eq(oracle.iter()));
+        for val in -500..=500 {
+            assert_eq!(tree.contains(&val), oracle.contains(&val));
+        }
+
+        // Empty it out completely, in a random order.
+        let mut values = oracle.into_iter().collect::<Vec<_>>();
+        values.shuffle(&mut rng);
+        for val in values {
+            assert!(tree.remove(&val));
+            check(&tree);
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.depth(), 0);
+    }
+
+    // The defaults.
+    let mut tree = MultiwayBTree::new(1);
+    for val in 2..=100 {
+        tree.insert(val);
+    }
+    check(&tree);
+    assert_eq!(tree.len(), 100);
+    assert_eq!(tree.min_degree(), 6);
+    assert_eq!(MultiwayBTree::<i32>::default().min_degree(), 6);
+
+    // Depth stays logarithmic in the minimum degree, even for sorted input.
+    let mut tree = MultiwayBTree::with_min_degree(16);
+    for val in 0..100_000 {
+        tree.insert(val);
+    }
+    check(&tree);
+    // Each node below the root has at least 16 children.
+    assert!(tree.depth() <= 5);
+}
+
+#[test]
+#[should_panic(expected = "minimum degree")]
+fn test_multiway_btree_min_degree() {
+    MultiwayBTree::<i32>::with_min_degree(1);
+}
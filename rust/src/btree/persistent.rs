@@ -0,0 +1,441 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+///
+/// PersistentTree
+///
+/// A balanced tree which is never changed once it's built. `insert` and `remove` hand back a new
+/// tree and leave the old one exactly as it was, so any copy you hold on to is a snapshot.
+///
+/// Copying the whole tree for every change would be O(n). Instead nodes are shared between
+/// versions with `Arc`: a change only makes new copies of the nodes on the path from the root
+/// down to where it happened (plus the odd rotation), and the new nodes point at all the same
+/// untouched sub-trees the old ones did. That's O(log n) new nodes per change, since the tree is
+/// kept AVL balanced just like `BTree::insert_balanced`.
+///
+/// Cloning a `PersistentTree` only copies the root pointer, and since it's `Arc` (not `Rc`) a
+/// snapshot can be handed off to readers on other threads while the writer carries on.
+///
+/// ```
+/// # use exercises::btree::PersistentTree;
+/// let empty = PersistentTree::empty();
+/// let one = empty.insert(1);
+/// let two = one.insert(2);
+///
+/// assert_eq!(empty.len(), 0);
+/// assert_eq!(one.iter().collect::<Vec<_>>(), vec![&1]);
+/// assert_eq!(two.iter().collect::<Vec<_>>(), vec![&1, &2]);
+///
+/// // Undo is just going back to an older version.
+/// let undone = one;
+/// assert_eq!(undone.contains(&2), false);
+/// ```
+///
+#[derive(Debug)]
+pub struct PersistentTree<T> {
+    root: Link<T>,
+}
+
+type Link<T> = Option<Arc<PNode<T>>>;
+
+///
+/// A single node in a [PersistentTree].
+///
+/// Laid out like a `BTree` node, with cached height and size, but nodes can have more than one
+/// parent (one in each version of the tree sharing them), and they are never changed after
+/// they're made.
+///
+#[derive(Debug)]
+struct PNode<T> {
+    value: T,
+    height: isize,
+    size: usize,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+///
+/// Cloning is O(1) whatever `T` is, we only copy the pointer to the root.
+///
+impl<T> Clone for PersistentTree<T> {
+    fn clone(&self) -> Self {
+        PersistentTree {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T: Ord + Clone> Default for PersistentTree<T> {
+    fn default() -> Self {
+        PersistentTree::empty()
+    }
+}
+
+impl<T: Ord + Clone> PersistentTree<T> {
+    pub fn new(val: T) -> PersistentTree<T> {
+        PersistentTree {
+            root: Some(make(None, val, None)),
+        }
+    }
+
+    pub fn empty() -> PersistentTree<T> {
+        PersistentTree { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, |node| node.size)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn depth(&self) -> isize {
+        height(&self.root)
+    }
+
+    /// Is `val` in the tree?
+    pub fn contains(&self, val: &T) -> bool {
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            node = match val.cmp(&n.value) {
+                Ordering::Equal => return true,
+                Ordering::Less => n.left.as_deref(),
+                Ordering::Greater => n.right.as_deref(),
+            };
+        }
+        false
+    }
+
+    /// A new tree with `val` in it. This tree doesn't change.
+    ///
+    /// We copy each node on the way down to where `val` goes, and rebalance the copies on the way
+    /// back up. The values on the path are cloned into the new nodes, everything else is shared.
+    ///
+    /// If `val` is already in the tree there's nothing to copy, we just hand back another pointer
+    /// to the same tree.
+    ///
+    /// ```
+    /// # use exercises::btree::PersistentTree;
+    /// let mut versions = vec![PersistentTree::empty()];
+    /// for val in 1..=7 {
+    ///     let next = versions.last().unwrap().insert(val);
+    ///     versions.push(next);
+    /// }
+    ///
+    /// assert_eq!(versions[3].len(), 3);
+    /// assert_eq!(versions[7].len(), 7);
+    /// assert_eq!(versions[7].depth(), 3);
+    /// ```
+    pub fn insert(&self, val: T) -> PersistentTree<T> {
+        match insert(&self.root, val) {
+            Some(root) => PersistentTree { root: Some(root) },
+            None => self.clone(),
+        }
+    }
+
+    /// A new tree without `val` in it. This tree doesn't change.
+    ///
+    /// The same as `BTree::remove_balanced`, but copying rather than changing the nodes on the
+    /// path, including the path to the in-order successor when the node we remove has two
+    /// children.
+    ///
+    /// ```
+    /// # use exercises::btree::PersistentTree;
+    /// let before = PersistentTree::new(2).insert(1).insert(3);
+    /// let after = before.remove(&2);
+    ///
+    /// assert_eq!(before.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// assert_eq!(after.iter().collect::<Vec<_>>(), vec![&1, &3]);
+    /// ```
+    pub fn remove(&self, val: &T) -> PersistentTree<T> {
+        match remove(&self.root, val) {
+            Some(root) => PersistentTree { root },
+            None => self.clone(),
+        }
+    }
+}
+
+impl<T> PersistentTree<T> {
+    /// In-order iterator, smallest value first.
+    pub fn iter(&self) -> PersistentIter<'_, T> {
+        let mut iter = PersistentIter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+}
+
+///
+/// In-order iterator over a [PersistentTree], the same explicit-stack walk as [Iter](super::Iter).
+///
+pub struct PersistentIter<'a, T> {
+    stack: Vec<&'a PNode<T>>,
+}
+
+impl<'a, T> PersistentIter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a PNode<T>>) {
+        while let Some(n) = node {
+            self.stack.push(n);
+            node = n.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T> Iterator for PersistentIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentTree<T> {
+    type Item = &'a T;
+    type IntoIter = PersistentIter<'a, T>;
+
+    fn into_iter(self) -> PersistentIter<'a, T> {
+        self.iter()
+    }
+}
+
+fn height<T>(link: &Link<T>) -> isize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+///
+/// Private API: make
+///
+/// A new node, with its height and size worked out from its children.
+///
+fn make<T>(left: Link<T>, value: T, right: Link<T>) -> Arc<PNode<T>> {
+    Arc::new(PNode {
+        height: 1 + std::cmp::max(height(&left), height(&right)),
+        size: 1 + size(&left) + size(&right),
+        value,
+        left,
+        right,
+    })
+}
+
+///
+/// Private API: balance
+///
+/// Like `make`, but fixes the AVL property if the two sides differ in height by 2.
+///
+/// It's the same four cases as `Node::rebalance`, but rather than rotating nodes in place we build
+/// the rotated shape out of new nodes. For a left-left case:
+///
+/// ```text
+///         v               l
+///        / \             / \
+///       l   r    =>    ll   v
+///      / \                 / \
+///    ll   lr             lr   r
+/// ```
+///
+/// `ll`, `lr` and `r` are shared as they are, only `l` and `v` need new nodes.
+///
+fn balance<T: Clone>(left: Link<T>, value: T, right: Link<T>) -> Arc<PNode<T>> {
+    if height(&left) > height(&right) + 1 {
+        let l = left.expect("a taller side can't be empty");
+        if height(&l.left) >= height(&l.right) {
+            let v = make(l.right.clone(), value, right);
+            make(l.left.clone(), l.value.clone(), Some(v))
+        } else {
+            let lr = l.right.as_ref().expect("the taller side can't be empty");
+            let new_l = make(l.left.clone(), l.value.clone(), lr.left.clone());
+            let v = make(lr.right.clone(), value, right);
+            make(Some(new_l), lr.value.clone(), Some(v))
+        }
+    } else if height(&right) > height(&left) + 1 {
+        let r = right.expect("a taller side can't be empty");
+        if height(&r.right) >= height(&r.left) {
+            let v = make(left, value, r.left.clone());
+            make(Some(v), r.value.clone(), r.right.clone())
+        } else {
+            let rl = r.left.as_ref().expect("the taller side can't be empty");
+            let v = make(left, value, rl.left.clone());
+            let new_r = make(rl.right.clone(), r.value.clone(), r.right.clone());
+            make(Some(v), rl.value.clone(), Some(new_r))
+        }
+    } else {
+        make(left, value, right)
+    }
+}
+
+///
+/// Private API: insert
+///
+/// The new root of the sub-tree with `val` added, or `None` if `val` was already there (and so
+/// nothing changed).
+///
+fn insert<T: Ord + Clone>(link: &Link<T>, val: T) -> Option<Arc<PNode<T>>> {
+    let node = match link {
+        Some(node) => node,
+        None => return Some(make(None, val, None)),
+    };
+
+    match val.cmp(&node.value) {
+        Ordering::Equal => None,
+        Ordering::Less => {
+            let left = insert(&node.left, val)?;
+            Some(balance(Some(left), node.value.clone(), node.right.clone()))
+        },
+        Ordering::Greater => {
+            let right = insert(&node.right, val)?;
+            Some(balance(node.left.clone(), node.value.clone(), Some(right)))
+        },
+    }
+}
+
+///
+/// Private API: remove
+///
+/// The new root of the sub-tree with `val` taken out, or `None` if `val` wasn't there.
+///
+fn remove<T: Ord + Clone>(link: &Link<T>, val: &T) -> Option<Link<T>> {
+    let node = link.as_ref()?;
+
+    match val.cmp(&node.value) {
+        Ordering::Less => {
+            let left = remove(&node.left, val)?;
+            Some(Some(balance(left, node.value.clone(), node.right.clone())))
+        },
+        Ordering::Greater => {
+            let right = remove(&node.right, val)?;
+            Some(Some(balance(node.left.clone(), node.value.clone(), right)))
+        },
+        Ordering::Equal => Some(match (&node.left, &node.right) {
+            (None, child) | (child, None) => child.clone(),
+            (left, Some(right)) => {
+                let (successor, right) = remove_min(right);
+                Some(balance(left.clone(), successor, right))
+            },
+        }),
+    }
+}
+
+///
+/// Private API: remove_min
+///
+/// The smallest value in the sub-tree, and a copy of the sub-tree without it.
+///
+fn remove_min<T: Clone>(node: &Arc<PNode<T>>) -> (T, Link<T>) {
+    match node.left {
+        None => (node.value.clone(), node.right.clone()),
+        Some(ref left) => {
+            let (min, left) = remove_min(left);
+            (min, Some(balance(left, node.value.clone(), node.right.clone())))
+        },
+    }
+}
+
+#[test]
+fn test_persistent_tree() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    // Sorted, AVL balanced, and the cached heights and sizes add up.
+    fn check<T: Ord>(tree: &PersistentTree<T>) {
+        fn walk<T: Ord>(link: &Link<T>, lower: Option<&T>, upper: Option<&T>) -> (isize, usize) {
+            let node = match link {
+                Some(node) => node,
+                None => return (0, 0),
+            };
+            assert!(lower.is_none_or(|lower| node.value > *lower));
+            assert!(upper.is_none_or(|upper| node.value < *upper));
+
+            let (left_height, left_size) = walk(&node.left, lower, Some(&node.value));
+            let (right_height, right_size) = walk(&node.right, Some(&node.value), upper);
+
+            assert!((left_height - right_height).abs() <= 1);
+            assert_eq!(node.height, 1 + std::cmp::max(left_height, right_height));
+            assert_eq!(node.size, 1 + left_size + right_size);
+
+            (node.height, node.size)
+        }
+
+        walk(&tree.root, None, None);
+    }
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-300, 300);
+
+    let mut versions = vec![(PersistentTree::empty(), BTreeSet::new())];
+
+    for _ in 0..1024 {
+        let (tree, oracle) = versions.last().unwrap();
+        let val = rng.sample(distr);
+
+        let mut oracle = oracle.clone();
+        let tree = if rng.gen_bool(0.7) {
+            oracle.insert(val);
+            tree.insert(val)
+        } else {
+            oracle.remove(&val);
+            tree.remove(&val)
+        };
+
+        check(&tree);
+        versions.push((tree, oracle));
+    }
+
+    // Every old version is still exactly what it was.
+    for (tree, oracle) in versions.iter() {
+        assert!(tree.iter().eq(oracle.iter()));
+        assert_eq!(tree.len(), oracle.len());
+    }
+
+    for val in -300..=300 {
+        let (tree, oracle) = versions.last().unwrap();
+        assert_eq!(tree.contains(&val), oracle.contains(&val));
+    }
+}
+
+#[test]
+fn test_persistent_sharing() {
+    // Nodes which only the new version points at are the ones it had to copy. Once we reach a
+    // node the old version points at too, everything below it is shared.
+    fn fresh<T>(link: &Link<T>) -> usize {
+        match link {
+            Some(node) if Arc::strong_count(node) == 1 => {
+                1 + fresh(&node.left) + fresh(&node.right)
+            },
+            _ => 0,
+        }
+    }
+
+    let mut tree = PersistentTree::empty();
+    for val in 0..1023 {
+        tree = tree.insert(val * 2);
+    }
+    assert_eq!(tree.depth(), 10);
+
+    for val in [1, 501, 1001, 2045] {
+        let next = tree.insert(val);
+        assert!(fresh(&next.root) <= next.depth() as usize + 2);
+
+        let next = tree.remove(&(val - 1));
+        assert!(fresh(&next.root) <= next.depth() as usize + 2);
+    }
+
+    // Nothing to change, nothing copied.
+    let same = tree.insert(2);
+    assert!(Arc::ptr_eq(same.root.as_ref().unwrap(), tree.root.as_ref().unwrap()));
+    let same = tree.remove(&3);
+    assert!(Arc::ptr_eq(same.root.as_ref().unwrap(), tree.root.as_ref().unwrap()));
+
+    // A snapshot can be read on another thread while we carry on.
+    let snapshot = tree.clone();
+    let reader = std::thread::spawn(move || snapshot.iter().count());
+    tree = tree.insert(1);
+    assert_eq!(reader.join().unwrap(), 1023);
+    assert_eq!(tree.len(), 1024);
+}
@@ -0,0 +1,599 @@
+use std::cmp::Ordering;
+
+use super::{Direction, ValidationError, Violation};
+
+///
+/// RedBlackTree
+///
+/// The other classic self-balancing tree, here so we can compare it with the AVL balancing
+/// `BTree::insert_balanced` does.
+///
+/// Instead of keeping track of heights, every node is colored red or black, and the tree sticks to
+/// these rules:
+/// 1. The root is black.
+/// 2. A red node never has a red child.
+/// 3. Every path from a node down to a missing child passes through the same number of black
+///    nodes (the "black height").
+///
+/// Rule 3 means the black nodes alone form a perfectly balanced tree, and rule 2 means red nodes
+/// can at most double the length of a path. So the longest path is never more than twice the
+/// shortest, and the depth is at most `2 * log2(n + 1)`.
+///
+/// That is looser than AVL (about `1.44 * log2(n)`), so lookups can take a little longer, but in
+/// exchange an insert needs at most two rotations and most of the fixing up is just recoloring.
+///
+/// ```
+/// # use exercises::btree::RedBlackTree;
+/// let mut t = RedBlackTree::new(1);
+/// for val in 2..=7 {
+///     t.insert(val);
+/// }
+///
+/// assert_eq!(t.len(), 7);
+/// assert_eq!(t.contains(&4), true);
+/// assert_eq!(t.balanced(), true);
+/// assert_eq!(t.depth(), 4);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct RedBlackTree<T> {
+    root: Option<Box<RbNode<T>>>,
+    len: usize,
+    rotations: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Color {
+    Red,
+    Black,
+}
+
+///
+/// A single node in a [RedBlackTree].
+///
+/// Nodes cache their height just like `BTree` nodes do, so `depth` is O(1) and we can compare
+/// the two trees' depths without walking them.
+///
+#[derive(Debug, Clone)]
+struct RbNode<T> {
+    value: T,
+    color: Color,
+    height: isize,
+    left: Option<Box<RbNode<T>>>,
+    right: Option<Box<RbNode<T>>>,
+}
+
+impl<T: Ord> Default for RedBlackTree<T> {
+    fn default() -> Self {
+        RedBlackTree::empty()
+    }
+}
+
+impl<T: Ord> RedBlackTree<T> {
+    pub fn new(val: T) -> RedBlackTree<T> {
+        let mut tree = RedBlackTree::empty();
+        tree.insert(val);
+        tree
+    }
+
+    pub fn empty() -> RedBlackTree<T> {
+        RedBlackTree {
+            root: None,
+            len: 0,
+            rotations: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Insert a value into the tree, keeping the red-black rules.
+    ///
+    /// A new node starts off red, which can't break rule 3, but it can break rule 2 if its parent
+    /// is red too. That gets fixed on the way back up, looking at the grandparent of the red pair:
+    /// * If the grandparent's other child (the "uncle") is red as well, we flip the colors: the
+    ///   grandparent turns red and its children turn black. Black heights stay the same, but now
+    ///   the grandparent might be a red child of a red node, so we keep going up.
+    /// * Otherwise we rotate the red pair up into the grandparent's place (twice if the pair bends
+    ///   a zig-zag), and color the new top of the sub-tree black. That fixes everything, nothing
+    ///   further up can have changed.
+    ///
+    /// Finally the root is always colored black, which is the one place rule 1 can be broken.
+    ///
+    /// Duplicate values are not stored, the same as `BTree`.
+    ///
+    /// ```
+    /// # use exercises::btree::RedBlackTree;
+    /// let mut t = RedBlackTree::empty();
+    /// t.insert(3);
+    /// t.insert(1);
+    /// t.insert(3);
+    ///
+    /// assert_eq!(t.len(), 2);
+    /// ```
+    pub fn insert(&mut self, val: T) {
+        if insert(&mut self.root, val, &mut self.rotations) {
+            self.len += 1;
+        }
+
+        if let Some(ref mut root) = self.root {
+            root.color = Color::Black;
+        }
+    }
+
+    /// Is `val` in the tree?
+    pub fn contains(&self, val: &T) -> bool {
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            node = match val.cmp(&n.value) {
+                Ordering::Equal => return true,
+                Ordering::Less => n.left.as_deref(),
+                Ordering::Greater => n.right.as_deref(),
+            };
+        }
+        false
+    }
+
+    /// The length of the longest path from the root, or 0 for an empty tree.
+    ///
+    /// Like `BTree::depth`, this is a cached value so it costs nothing.
+    pub fn depth(&self) -> isize {
+        self.root.as_ref().map_or(0, |node| node.height)
+    }
+
+    /// Is the tree balanced the way a red-black tree promises to be?
+    ///
+    /// That is not the same as `BTree::balanced`: the two sides of a red-black tree can differ in
+    /// depth by a lot more than one. What it does promise is that the colors follow the rules,
+    /// which keeps the longest path within twice the shortest. So this just asks
+    /// `validate_colors`.
+    ///
+    /// ```
+    /// # use exercises::btree::RedBlackTree;
+    /// let mut t = RedBlackTree::empty();
+    /// for val in 0..100 {
+    ///     t.insert(val);
+    ///     assert_eq!(t.balanced(), true);
+    /// }
+    /// ```
+    pub fn balanced(&self) -> bool {
+        self.validate_colors().is_ok()
+    }
+
+    /// How many rotations inserting has done over the life of the tree.
+    ///
+    /// Handy for comparing how much work red-black and AVL balancing do on the same values, see
+    /// `BTree::rotations` for the AVL side.
+    ///
+    /// ```
+    /// # use exercises::btree::RedBlackTree;
+    /// let mut t = RedBlackTree::empty();
+    /// t.insert(1);
+    /// t.insert(2);
+    ///
+    /// assert_eq!(t.rotations(), 0);
+    ///
+    /// // Three in a row is a red pair with no uncle, so it needs a rotation.
+    /// t.insert(3);
+    ///
+    /// assert_eq!(t.rotations(), 1);
+    /// ```
+    pub fn rotations(&self) -> usize {
+        self.rotations
+    }
+
+    /// Checks the three red-black rules, and the cached heights, pointing at the first node
+    /// which breaks one.
+    ///
+    /// Black heights are counted bottom up, so every node gets compared with both of its
+    /// children's counts.
+    ///
+    /// ```
+    /// # use exercises::btree::RedBlackTree;
+    /// let mut t = RedBlackTree::empty();
+    /// for val in [5, 2, 8, 1, 9] {
+    ///     t.insert(val);
+    /// }
+    ///
+    /// assert_eq!(t.validate_colors(), Ok(()));
+    /// ```
+    pub fn validate_colors(&self) -> Result<(), ValidationError> {
+        let root = match self.root {
+            Some(ref root) => root,
+            None => return Ok(()),
+        };
+
+        if root.color == Color::Red {
+            return Err(ValidationError {
+                path: Vec::new(),
+                violation: Violation::RedRoot,
+            });
+        }
+
+        root.black_height(&mut Vec::new()).map(|_| ())
+    }
+}
+
+///
+/// Private API: insert
+///
+/// Inserts `val` into the sub-tree hanging off of `slot`, returning whether it was added.
+///
+/// Works on the slot, like `remove_by` for `BTree`, since the very first node has to be put into
+/// an empty slot.
+///
+fn insert<T: Ord>(slot: &mut Option<Box<RbNode<T>>>, val: T, rotations: &mut usize) -> bool {
+    let node = match slot {
+        Some(node) => node,
+        None => {
+            *slot = Some(Box::new(RbNode::new(val)));
+            return true;
+        },
+    };
+
+    let inserted = match val.cmp(&node.value) {
+        Ordering::Less => insert(&mut node.left, val, rotations),
+        Ordering::Greater => insert(&mut node.right, val, rotations),
+        Ordering::Equal => false,
+    };
+
+    if inserted {
+        node.fix(rotations);
+    }
+
+    inserted
+}
+
+impl<T> RbNode<T> {
+    fn new(val: T) -> RbNode<T> {
+        RbNode {
+            value: val,
+            color: Color::Red,
+            height: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    ///
+    /// Private API: fix
+    ///
+    /// Called on every node on the way back up from an insert, with this node as the grandparent
+    /// of any red pair below it.
+    ///
+    fn fix(&mut self, rotations: &mut usize) {
+        self.update();
+
+        let red_pair = |child: &Option<Box<RbNode<T>>>| {
+            child.as_ref().is_some_and(|child| child.color == Color::Red && child.has_red_child())
+        };
+        let left_pair = red_pair(&self.left);
+        let right_pair = red_pair(&self.right);
+
+        if !(left_pair || right_pair) {
+            return;
+        }
+
+        if is_red(&self.left) && is_red(&self.right) {
+            // A red uncle, so recolor and let our parent deal with it.
+            self.color = Color::Red;
+            self.color_children(Color::Black);
+            return;
+        }
+
+        if left_pair {
+            if let Some(ref mut left) = self.left {
+                if is_red(&left.right) {
+                    left.rotate_left();
+                    *rotations += 1;
+                }
+            }
+            self.rotate_right();
+        } else {
+            if let Some(ref mut right) = self.right {
+                if is_red(&right.left) {
+                    right.rotate_right();
+                    *rotations += 1;
+                }
+            }
+            self.rotate_left();
+        }
+        *rotations += 1;
+
+        // The red child is on top now, with the old (black) grandparent as one of its children.
+        self.color = Color::Black;
+        self.color_children(Color::Red);
+    }
+
+    fn color_children(&mut self, color: Color) {
+        for child in self.left.iter_mut().chain(self.right.iter_mut()) {
+            child.color = color;
+        }
+    }
+
+    fn has_red_child(&self) -> bool {
+        is_red(&self.left) || is_red(&self.right)
+    }
+
+    ///
+    /// Private API: update
+    ///
+    /// Re-calculates the cached height from the children's.
+    ///
+    fn update(&mut self) {
+        let left = self.left.as_ref().map_or(0, |node| node.height);
+        let right = self.right.as_ref().map_or(0, |node| node.height);
+        self.height = 1 + std::cmp::max(left, right);
+    }
+
+    ///
+    /// Private API: rotate_right
+    ///
+    /// The same in-place swap trick as `BTree`'s rotations. Colors travel with the values, the
+    /// caller recolors afterwards.
+    ///
+    fn rotate_right(&mut self) {
+        let mut pivot = match self.left.take() {
+            Some(node) => node,
+            None => return,
+        };
+
+        self.left = pivot.right.take();
+        self.update();
+
+        std::mem::swap(self, &mut *pivot);
+
+        self.right = Some(pivot);
+        self.update();
+    }
+
+    ///
+    /// Private API: rotate_left
+    ///
+    /// The mirror image of `rotate_right`.
+    ///
+    fn rotate_left(&mut self) {
+        let mut pivot = match self.right.take() {
+            Some(node) => node,
+            None => return,
+        };
+
+        self.right = pivot.left.take();
+        self.update();
+
+        std::mem::swap(self, &mut *pivot);
+
+        self.left = Some(pivot);
+        self.update();
+    }
+
+    ///
+    /// Private API: black_height
+    ///
+    /// Checks this sub-tree, returning the number of black nodes on every path down from here
+    /// (counting the missing children at the bottom as black, and this node itself).
+    ///
+    /// `path` is the way here from the root, for error messages. This recurses, but a tree that
+    /// follows the rules is only ever O(log n) deep, and we stop at the first broken node.
+    ///
+    fn black_height(&self, path: &mut Vec<Direction>) -> Result<usize, ValidationError> {
+        let error = |path: &Vec<Direction>, violation| ValidationError {
+            path: path.clone(),
+            violation,
+        };
+
+        if self.color == Color::Red && self.has_red_child() {
+            return Err(error(path, Violation::RedRed));
+        }
+
+        let left = self.left.as_ref().map_or(0, |node| node.height);
+        let right = self.right.as_ref().map_or(0, |node| node.height);
+        let expected = 1 + std::cmp::max(left, right);
+        if self.height != expected {
+            return Err(error(path, Violation::Height {
+                cached: self.height,
+                expected,
+            }));
+        }
+
+        let left = black_height(&self.left, Direction::Left, path)?;
+        let right = black_height(&self.right, Direction::Right, path)?;
+
+        if left != right {
+            return Err(error(path, Violation::BlackHeight { left, right }));
+        }
+
+        Ok(left + if self.color == Color::Black { 1 } else { 0 })
+    }
+}
+
+///
+/// Private API: black_height
+///
+/// The black height of the child in `slot`, which is one level further down `path`. A missing
+/// child counts as a single black node.
+///
+fn black_height<T>(
+    slot: &Option<Box<RbNode<T>>>,
+    direction: Direction,
+    path: &mut Vec<Direction>,
+) -> Result<usize, ValidationError> {
+    match slot {
+        Some(child) => {
+            path.push(direction);
+            let height = child.black_height(path)?;
+            path.pop();
+            Ok(height)
+        },
+        None => Ok(1),
+    }
+}
+
+fn is_red<T>(slot: &Option<Box<RbNode<T>>>) -> bool {
+    slot.as_ref().is_some_and(|node| node.color == Color::Red)
+}
+
+#[test]
+fn test_red_black() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn in_order<T: Clone>(slot: &Option<Box<RbNode<T>>>, out: &mut Vec<T>) {
+        if let Some(ref node) = slot {
+            in_order(&node.left, out);
+            out.push(node.value.clone());
+            in_order(&node.right, out);
+        }
+    }
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-1000, 1000);
+
+    let mut t = RedBlackTree::empty();
+    let mut oracle = BTreeSet::new();
+
+    assert_eq!(t.depth(), 0);
+    assert!(t.balanced());
+
+    for _ in 0..1024 {
+        let val = rng.sample(distr);
+        t.insert(val);
+        oracle.insert(val);
+
+        assert_eq!(t.validate_colors(), Ok(()));
+    }
+
+    let mut values = Vec::new();
+    in_order(&t.root, &mut values);
+    assert_eq!(values, oracle.iter().copied().collect::<Vec<_>>());
+    assert_eq!(t.len(), oracle.len());
+
+    for val in -1000..=1000 {
+        assert_eq!(t.contains(&val), oracle.contains(&val));
+    }
+
+    // Sorted input, the worst case for a plain tree.
+    let mut t = RedBlackTree::empty();
+    for val in 0..4096 {
+        t.insert(val);
+    }
+    assert_eq!(t.validate_colors(), Ok(()));
+    assert!(t.depth() <= 2 * 13);
+}
+
+#[test]
+fn test_validate_colors() {
+    let mut t = RedBlackTree::empty();
+    for val in [4, 2, 6, 1, 3, 5, 7, 8] {
+        t.insert(val);
+    }
+    assert_eq!(t.validate_colors(), Ok(()));
+
+    let mut red_root = t.clone();
+    if let Some(ref mut root) = red_root.root {
+        root.color = Color::Red;
+    }
+    let err = red_root.validate_colors().unwrap_err();
+    assert_eq!(err.violation, Violation::RedRoot);
+    assert_eq!(err.to_string(), "red root at root");
+    assert!(!red_root.balanced());
+
+    //   4B
+    //  /   \
+    // 2B    6R
+    // / \   / \
+    // 1R 3R 5B 7B
+    //            \
+    //             8R
+    //
+    // Turning 7 red makes it a red child of 6 (and a red parent of 8).
+    let mut red_red = t.clone();
+    if let Some(ref mut root) = red_red.root {
+        if let Some(ref mut six) = root.right {
+            if let Some(ref mut seven) = six.right {
+                seven.color = Color::Red;
+            }
+        }
+    }
+    let err = red_red.validate_colors().unwrap_err();
+    assert_eq!(err.path, vec![Direction::Right]);
+    assert_eq!(err.violation, Violation::RedRed);
+    assert_eq!(err.to_string(), "red node with a red child at root.right");
+
+    // Turning a red leaf black leaves its parent lopsided.
+    let mut lopsided = t.clone();
+    if let Some(ref mut root) = lopsided.root {
+        if let Some(ref mut two) = root.left {
+            if let Some(ref mut one) = two.left {
+                one.color = Color::Black;
+            }
+        }
+    }
+    let err = lopsided.validate_colors().unwrap_err();
+    assert_eq!(err.path, vec![Direction::Left]);
+    assert_eq!(err.violation, Violation::BlackHeight { left: 2, right: 1 });
+    assert_eq!(err.to_string(), "black height 2 on the left but 1 on the right at root.left");
+}
+
+#[test]
+fn test_red_black_vs_avl() {
+    use super::BTree;
+    use rand::prelude::*;
+
+    let mut rng = thread_rng();
+
+    // The same kinds of input the sort tests use: lots of small random values (with plenty of
+    // duplicates), a wider spread of random values, and already sorted values.
+    let small = rand::distributions::Uniform::new_inclusive(1, 100);
+    let wide = rand::distributions::Uniform::new_inclusive(-100_000, 100_000);
+
+    let workloads: Vec<(&str, Vec<i32>)> = vec![
+        ("random 1..=100", (1..1024).map(|_| rng.sample(small)).collect()),
+        ("random wide", (1..1024).map(|_| rng.sample(wide)).collect()),
+        ("sorted", (1..1024).collect()),
+        ("reversed", (1..1024).rev().collect()),
+    ];
+
+    for (name, values) in workloads {
+        let mut avl = BTree::empty();
+        for &val in values.iter() {
+            avl.insert_balanced(val);
+        }
+
+        let mut rb = RedBlackTree::empty();
+        for &val in values.iter() {
+            rb.insert(val);
+        }
+
+        assert_eq!(avl.len(), rb.len(), "{}", name);
+        assert_eq!(rb.validate_colors(), Ok(()), "{}", name);
+        assert!(avl.is_height_balanced(), "{}", name);
+
+        let n = avl.len() as f64;
+
+        // The height guarantees: AVL is under 1.44 * log2(n + 2), red-black is at most
+        // 2 * log2(n + 1).
+        assert!((avl.depth() as f64) < 1.44 * (n + 2.0).log2(), "{}", name);
+        assert!((rb.depth() as f64) <= 2.0 * (n + 1.0).log2(), "{}", name);
+
+        // Either one does at most a double rotation per value actually inserted.
+        assert!(avl.rotations() <= 2 * avl.len(), "{}", name);
+        assert!(rb.rotations() <= 2 * rb.len(), "{}", name);
+
+        // Sorted input (either way round) is the worst case for balancing: every insert lands on
+        // the same edge, so most of them need fixing up. AVL's stricter rules keep the tree
+        // shallower, and red-black's looser ones get away with fewer rotations.
+        if name == "sorted" || name == "reversed" {
+            assert!(avl.rotations() >= avl.len() / 2, "{}", name);
+            assert!(rb.rotations() >= rb.len() / 2, "{}", name);
+            assert!(avl.depth() <= rb.depth(), "{}", name);
+            assert!(rb.rotations() <= avl.rotations(), "{}", name);
+        }
+    }
+}
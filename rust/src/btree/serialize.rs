@@ -0,0 +1,554 @@
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use super::{BTree, Node, ValidationError};
+
+///
+/// Saving and loading trees
+///
+/// Both formats are the same idea: walk the tree pre-order (node, then left, then right) and
+/// write down a marker wherever a child is missing. That is enough to rebuild the exact shape of
+/// the tree, not just its values, which matters for a tree whose shape depends on the order
+/// things were inserted in.
+///
+/// The text format wraps every node in parentheses, `-` is a missing child:
+///
+/// ```text
+/// (4 (2 (1 - -) (3 - -)) (6 - (7 - -)))
+/// ```
+///
+/// The JSON format (via serde, behind the `serde` feature) is a flat array with every value
+/// wrapped in a one element array, and `null` for a missing child:
+///
+/// ```text
+/// [[4], [2], [1], null, null, [3], null, null, [6], null, [7], null, null]
+/// ```
+///
+/// A flat array rather than nested objects means a deep tree doesn't run into the nesting limits
+/// JSON parsers have. The extra brackets are there so a missing child can't be mistaken for a
+/// value which is itself `null`, like the `None`s in a `BTree<Option<T>>`.
+///
+/// Loading either format checks the result with `validate`, so a file which is shaped like a
+/// tree but isn't sorted like one gets rejected.
+///
+impl<T: fmt::Display> BTree<T> {
+    /// Write the tree in the parenthesized pre-order text format.
+    ///
+    /// Values are written with `Display`. Any value which would confuse the parser (one with
+    /// spaces, parentheses or quotes in it, an empty one, or a lone `-`) gets wrapped in double
+    /// quotes, with `"` and `\` escaped by a `\`.
+    ///
+    /// Use `str::parse` (this tree implements `FromStr`) to read it back in.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(2);
+    /// my_tree.insert(1);
+    ///
+    /// let text = my_tree.to_text();
+    /// assert_eq!(text, "(2 (1 - -) -)");
+    ///
+    /// let loaded: BTree<i32> = text.parse().unwrap();
+    /// assert_eq!(loaded, my_tree);
+    /// ```
+    pub fn to_text(&self) -> String {
+        enum Token<'a, T> {
+            Node(&'a Node<T>),
+            Missing,
+            Close,
+        }
+
+        let mut text = String::new();
+        let mut stack = vec![match self.root {
+            Some(ref root) => Token::Node(root),
+            None => Token::Missing,
+        }];
+
+        while let Some(token) = stack.pop() {
+            match token {
+                Token::Node(node) => {
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text.push('(');
+                    text.push_str(&quote(&node.value.to_string()));
+
+                    stack.push(Token::Close);
+                    for child in [&node.right, &node.left] {
+                        stack.push(match child {
+                            Some(child) => Token::Node(child),
+                            None => Token::Missing,
+                        });
+                    }
+                },
+                Token::Missing => {
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text.push('-');
+                },
+                Token::Close => text.push(')'),
+            }
+        }
+
+        text
+    }
+}
+
+///
+/// Quote a value for the text format, if it needs it.
+///
+fn quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value != "-"
+        && !value.chars().any(|c| c.is_whitespace() || "()\"\\".contains(c));
+
+    if plain {
+        return value.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+///
+/// Why a tree could not be loaded.
+///
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    /// The input is not shaped like a tree.
+    /// `offset` is a byte offset into the text, or an index into the JSON array.
+    Syntax { offset: usize, message: String },
+    /// A value could not be parsed, the message is from the value's `FromStr` error.
+    Value { offset: usize, message: String },
+    /// The input is shaped like a tree, but the values are not in order.
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax { offset, message } => {
+                write!(f, "syntax error at {}: {}", offset, message)
+            },
+            ParseError::Value { offset, message } => {
+                write!(f, "bad value at {}: {}", offset, message)
+            },
+            ParseError::Invalid(err) => write!(f, "invalid tree: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<T> FromStr for BTree<T>
+where
+    T: Ord + FromStr,
+    T::Err: fmt::Display,
+{
+    type Err = ParseError;
+
+    /// Read a tree written by `to_text`.
+    ///
+    /// We check the parentheses add up as we go: every `(` has to be followed by a value and
+    /// then exactly two children before its `)`. Along the way we note down the pre-order list
+    /// of values and missing children, and then hand that to the same builder the JSON format
+    /// uses.
+    fn from_str(text: &str) -> Result<BTree<T>, ParseError> {
+        let syntax = |offset, message: &str| ParseError::Syntax {
+            offset,
+            message: message.to_string(),
+        };
+
+        let mut items = Vec::new();
+        // How many children each currently open node has so far.
+        let mut open: Vec<u8> = Vec::new();
+        let mut done = false;
+
+        let mut tokens = Tokens { text, offset: 0 };
+
+        while let Some((offset, token)) = tokens.next_token()? {
+            if done {
+                return Err(syntax(offset, "unexpected input after the end of the tree"));
+            }
+
+            // A `-` or a `)` finishes off a child of whichever node is open around it.
+            let finished_child = match token {
+                Raw::Open => {
+                    if open.last() == Some(&2) {
+                        return Err(syntax(offset, "more than two children"));
+                    }
+                    let (offset, value) = match tokens.next_token()? {
+                        Some((offset, Raw::Value(value))) => (offset, value),
+                        Some((offset, _)) => return Err(syntax(offset, "expected a value")),
+                        None => return Err(syntax(text.len(), "expected a value")),
+                    };
+                    let value = value.parse::<T>().map_err(|err| ParseError::Value {
+                        offset,
+                        message: err.to_string(),
+                    })?;
+                    items.push(Some(value));
+                    open.push(0);
+                    false
+                },
+                Raw::Missing => {
+                    items.push(None);
+                    true
+                },
+                Raw::Close => {
+                    match open.pop() {
+                        Some(2) => (),
+                        Some(_) => return Err(syntax(offset, "expected two children")),
+                        None => return Err(syntax(offset, "unmatched ')'")),
+                    }
+                    true
+                },
+                Raw::Value(_) => return Err(syntax(offset, "expected '(' or '-'")),
+            };
+
+            if finished_child {
+                match open.last_mut() {
+                    Some(&mut 2) => return Err(syntax(offset, "more than two children")),
+                    Some(children) => *children += 1,
+                    None => done = true,
+                }
+            }
+        }
+
+        if !done {
+            return Err(syntax(text.len(), "unexpected end of input"));
+        }
+
+        from_pre_order(items)
+    }
+}
+
+///
+/// Tokens of the text format. Values are still raw text here.
+///
+enum Raw {
+    Open,
+    Close,
+    Missing,
+    Value(String),
+}
+
+struct Tokens<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn next_token(&mut self) -> Result<Option<(usize, Raw)>, ParseError> {
+        let rest = &self.text[self.offset..];
+        let trimmed = rest.trim_start();
+        let start = self.offset + (rest.len() - trimmed.len());
+
+        let mut chars = trimmed.char_indices();
+        let (_, first) = match chars.next() {
+            Some(c) => c,
+            None => {
+                self.offset = self.text.len();
+                return Ok(None);
+            },
+        };
+
+        let (token, len) = match first {
+            '(' => (Raw::Open, 1),
+            ')' => (Raw::Close, 1),
+            '"' => {
+                let mut value = String::new();
+                let mut escaped = false;
+                let mut end = None;
+                for (i, c) in chars {
+                    if escaped {
+                        value.push(c);
+                        escaped = false;
+                    } else if c == '\\' {
+                        escaped = true;
+                    } else if c == '"' {
+                        end = Some(i + 1);
+                        break;
+                    } else {
+                        value.push(c);
+                    }
+                }
+                match end {
+                    Some(end) => (Raw::Value(value), end),
+                    None => {
+                        return Err(ParseError::Syntax {
+                            offset: start,
+                            message: "unterminated quote".to_string(),
+                        })
+                    },
+                }
+            },
+            _ => {
+                let end = trimmed
+                    .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    .unwrap_or(trimmed.len());
+                let value = &trimmed[..end];
+                if value == "-" {
+                    (Raw::Missing, 1)
+                } else {
+                    (Raw::Value(value.to_string()), end)
+                }
+            },
+        };
+
+        self.offset = start + len;
+        Ok(Some((start, token)))
+    }
+}
+
+///
+/// Private API: from_pre_order
+///
+/// Builds a tree from a pre-order list of values, with `None` for missing children.
+///
+/// Building top down would mean holding on to a `&mut` into the half built tree, so instead we
+/// go through the list backwards. Backwards, a node's right sub-tree comes first, then its left
+/// sub-tree, then the node itself. So by the time we get to a node, its two finished sub-trees
+/// are sitting on top of the stack, ready to be popped off and attached.
+///
+/// That also means children are always done before their parents, so we can fill in the cached
+/// height and size as we go.
+///
+fn from_pre_order<T: Ord>(items: Vec<Option<T>>) -> Result<BTree<T>, ParseError> {
+    let len = items.len();
+    let mut stack: Vec<Option<Box<Node<T>>>> = Vec::new();
+
+    for (offset, item) in items.into_iter().enumerate().rev() {
+        match item {
+            None => stack.push(None),
+            Some(value) => {
+                let (left, right) = match (stack.pop(), stack.pop()) {
+                    (Some(left), Some(right)) => (left, right),
+                    _ => {
+                        return Err(ParseError::Syntax {
+                            offset,
+                            message: "node is missing its children".to_string(),
+                        })
+                    },
+                };
+                let mut node = Node::new(value);
+                node.left = left;
+                node.right = right;
+                node.update();
+                stack.push(Some(Box::new(node)));
+            },
+        }
+    }
+
+    let root = match (stack.pop(), stack.is_empty()) {
+        (Some(root), true) => root,
+        (None, _) => {
+            return Err(ParseError::Syntax {
+                offset: 0,
+                message: "no tree to read".to_string(),
+            })
+        },
+        (Some(_), false) => {
+            return Err(ParseError::Syntax {
+                offset: len,
+                message: "more than one tree".to_string(),
+            })
+        },
+    };
+
+    let tree = BTree { root, rotations: 0 };
+    tree.validate().map_err(ParseError::Invalid)?;
+    Ok(tree)
+}
+
+///
+/// Serializes as a flat pre-order sequence, with `None` for missing children and every value
+/// wrapped in a one element tuple.
+///
+#[cfg(feature = "serde")]
+impl<T: Serialize> Serialize for BTree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(None)?;
+
+        let mut stack = vec![self.root.as_deref()];
+        while let Some(node) = stack.pop() {
+            match node {
+                Some(node) => {
+                    seq.serialize_element(&Some((&node.value,)))?;
+                    stack.push(node.right.as_deref());
+                    stack.push(node.left.as_deref());
+                },
+                None => seq.serialize_element(&None::<(&T,)>)?,
+            }
+        }
+
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> Deserialize<'de> for BTree<T>
+where
+    T: Ord + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BTree<T>, D::Error> {
+        deserializer.deserialize_seq(PreOrderVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+struct PreOrderVisitor<T>(PhantomData<T>);
+
+#[cfg(feature = "serde")]
+impl<'de, T> Visitor<'de> for PreOrderVisitor<T>
+where
+    T: Ord + Deserialize<'de>,
+{
+    type Value = BTree<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a pre-order sequence of wrapped values, with nulls for missing children")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<BTree<T>, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element::<Option<(T,)>>()? {
+            items.push(item.map(|(value,)| value));
+        }
+        from_pre_order(items).map_err(de::Error::custom)
+    }
+}
+
+#[test]
+fn test_text_round_trip() {
+    use rand::prelude::*;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-1000, 1000);
+
+    let mut t = BTree::empty();
+
+    assert_eq!(t.to_text(), "-");
+    assert_eq!("-".parse::<BTree<i32>>(), Ok(BTree::empty()));
+
+    for _ in 0..256 {
+        t.insert(rng.sample(distr));
+    }
+
+    assert_eq!(t.to_text().parse::<BTree<i32>>(), Ok(t.clone()));
+
+    let t = "(4 (2 (1 - -) (3 - -)) (6 - (7 - -)))".parse::<BTree<i32>>().unwrap();
+    assert_eq!(t.to_text(), "(4 (2 (1 - -) (3 - -)) (6 - (7 - -)))");
+    assert_eq!(t.len(), 6);
+    assert_eq!(t.depth(), 3);
+
+    // Whitespace is flexible.
+    assert_eq!("  ( 4(2 - -)\n-)  ".parse::<BTree<i32>>(), "(4 (2 - -) -)".parse());
+
+    // Values that need quoting.
+    let mut t = BTree::empty();
+    for val in ["a b", "", "-", "(x)", "say \"hi\"", "back\\slash", "plain"] {
+        t.insert(String::from(val));
+    }
+    assert_eq!(t.to_text().parse::<BTree<String>>(), Ok(t));
+}
+
+#[test]
+fn test_text_errors() {
+    use super::{Direction, Violation};
+
+    let parse = |text: &str| text.parse::<BTree<i32>>().unwrap_err();
+
+    let syntax = |offset, message: &str| ParseError::Syntax {
+        offset,
+        message: message.to_string(),
+    };
+
+    assert_eq!(parse(""), syntax(0, "unexpected end of input"));
+    assert_eq!(parse("(1 - -"), syntax(6, "unexpected end of input"));
+    assert_eq!(parse("(1 -)"), syntax(4, "expected two children"));
+    assert_eq!(parse("(1 - - -)"), syntax(7, "more than two children"));
+    assert_eq!(parse("(1 - - (2 - -))"), syntax(7, "more than two children"));
+    assert_eq!(parse("(1 - -))"), syntax(7, "unexpected input after the end of the tree"));
+    assert_eq!(parse(")"), syntax(0, "unmatched ')'"));
+    assert_eq!(parse("1"), syntax(0, "expected '(' or '-'"));
+    assert_eq!(parse("(- - -)"), syntax(1, "expected a value"));
+    assert_eq!(parse("(\"1 - -)"), syntax(1, "unterminated quote"));
+
+    match parse("(x - -)") {
+        ParseError::Value { offset: 1, .. } => (),
+        err => panic!("unexpected error {:?}", err),
+    }
+
+    // Shaped fine, but 6 can't be in the left sub-tree of 5.
+    match parse("(5 (3 - (6 - -)) -)") {
+        ParseError::Invalid(err) => {
+            assert_eq!(err.path, vec![Direction::Left, Direction::Right]);
+            assert_eq!(err.violation, Violation::OutOfOrder);
+        },
+        err => panic!("unexpected error {:?}", err),
+    }
+
+    // Duplicates are out of order too.
+    match parse("(5 (5 - -) -)") {
+        ParseError::Invalid(_) => (),
+        err => panic!("unexpected error {:?}", err),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip() {
+    let mut t = BTree::new(4);
+    for val in [2, 6, 1, 3, 7] {
+        t.insert(val);
+    }
+
+    let json = serde_json::to_string(&t).unwrap();
+    assert_eq!(json, "[[4],[2],[1],null,null,[3],null,null,[6],null,[7],null,null]");
+    assert_eq!(serde_json::from_str::<BTree<i32>>(&json).unwrap(), t);
+
+    let empty: BTree<i32> = BTree::empty();
+    assert_eq!(serde_json::to_string(&empty).unwrap(), "[null]");
+    assert_eq!(serde_json::from_str::<BTree<i32>>("[null]").unwrap(), empty);
+
+    // A deep, degenerate tree is still a flat array.
+    let mut t = BTree::empty();
+    for val in 0..1_000 {
+        t.insert(val);
+    }
+    let json = serde_json::to_string(&t).unwrap();
+    assert_eq!(serde_json::from_str::<BTree<i32>>(&json).unwrap(), t);
+
+    assert!(serde_json::from_str::<BTree<i32>>("[]").is_err());
+    assert!(serde_json::from_str::<BTree<i32>>("[[1], null]").is_err());
+    assert!(serde_json::from_str::<BTree<i32>>("[[1], null, null, null]").is_err());
+    assert!(serde_json::from_str::<BTree<i32>>("[[1], [2], null, null, null]").is_err());
+    assert!(serde_json::from_str::<BTree<i32>>("[1, null, null]").is_err());
+
+    let err = serde_json::from_str::<BTree<i32>>("[[1], null, [0], null, null]").unwrap_err();
+    assert!(err.to_string().contains("invalid tree: value out of order at root.right"));
+
+    // Values which are themselves null don't get mixed up with missing children.
+    let mut t = BTree::new(Some(1));
+    t.insert(None);
+    t.insert(Some(0));
+    let json = serde_json::to_string(&t).unwrap();
+    assert_eq!(json, "[[1],[null],null,[0],null,null,null]");
+    assert_eq!(serde_json::from_str::<BTree<Option<i32>>>(&json).unwrap(), t);
+}
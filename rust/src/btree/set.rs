@@ -0,0 +1,248 @@
+use std::cmp::Ordering;
+
+use super::{build_balanced, BTree};
+
+///
+/// Set algebra
+///
+/// Every one of these walks both trees in order at the same time, like the merge step of a merge
+/// sort. Both walks come out sorted, so at each step we only have to compare the two values at the
+/// front: the smaller one can't be in the other tree, and if they are equal it is in both.
+///
+/// The operations which build a new tree collect the values they keep (already sorted, no
+/// duplicates) and hand them to the same builder as `from_sorted`, so the result is perfectly
+/// balanced no matter how lopsided the inputs were.
+///
+/// All of them are O(n + m), the checks can stop early as soon as they know the answer.
+///
+impl<T: Ord> BTree<T> {
+    /// Every value which is in either tree.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let a: BTree<i32> = vec![1, 2, 3].into_iter().collect();
+    /// let b: BTree<i32> = vec![3, 4].into_iter().collect();
+    ///
+    /// assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4]);
+    /// ```
+    pub fn union(&self, other: &BTree<T>) -> BTree<T>
+    where
+        T: Clone,
+    {
+        self.merge(other, |in_self, in_other| in_self || in_other)
+    }
+
+    /// Every value which is in both trees.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let a: BTree<i32> = vec![1, 2, 3].into_iter().collect();
+    /// let b: BTree<i32> = vec![2, 3, 4].into_iter().collect();
+    ///
+    /// assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), vec![&2, &3]);
+    /// ```
+    pub fn intersection(&self, other: &BTree<T>) -> BTree<T>
+    where
+        T: Clone,
+    {
+        self.merge(other, |in_self, in_other| in_self && in_other)
+    }
+
+    /// Every value which is in this tree but not in `other`.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let a: BTree<i32> = vec![1, 2, 3].into_iter().collect();
+    /// let b: BTree<i32> = vec![2, 3, 4].into_iter().collect();
+    ///
+    /// assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), vec![&1]);
+    /// ```
+    pub fn difference(&self, other: &BTree<T>) -> BTree<T>
+    where
+        T: Clone,
+    {
+        self.merge(other, |in_self, in_other| in_self && !in_other)
+    }
+
+    /// Every value which is in exactly one of the two trees.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let a: BTree<i32> = vec![1, 2, 3].into_iter().collect();
+    /// let b: BTree<i32> = vec![2, 3, 4].into_iter().collect();
+    ///
+    /// assert_eq!(a.symmetric_difference(&b).iter().collect::<Vec<_>>(), vec![&1, &4]);
+    /// ```
+    pub fn symmetric_difference(&self, other: &BTree<T>) -> BTree<T>
+    where
+        T: Clone,
+    {
+        self.merge(other, |in_self, in_other| in_self != in_other)
+    }
+
+    /// Is every value in this tree also in `other`?
+    ///
+    /// A tree with more values than `other` can't be a subset of it, so we can tell that without
+    /// looking at any values at all.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let a: BTree<i32> = vec![1, 3].into_iter().collect();
+    /// let b: BTree<i32> = vec![1, 2, 3].into_iter().collect();
+    ///
+    /// assert_eq!(a.is_subset(&b), true);
+    /// assert_eq!(b.is_subset(&a), false);
+    /// assert_eq!(BTree::empty().is_subset(&a), true);
+    /// ```
+    pub fn is_subset(&self, other: &BTree<T>) -> bool {
+        if self.len() > other.len() {
+            return false;
+        }
+
+        let mut theirs = other.iter().peekable();
+        for val in self.iter() {
+            // Skip past everything in `other` which is smaller, it can't match.
+            while theirs.next_if(|other| *other < val).is_some() {}
+
+            if theirs.next_if_eq(&val).is_none() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Do the two trees have no values in common?
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let a: BTree<i32> = vec![1, 3].into_iter().collect();
+    /// let b: BTree<i32> = vec![2, 4].into_iter().collect();
+    ///
+    /// assert_eq!(a.is_disjoint(&b), true);
+    /// assert_eq!(a.is_disjoint(&a), false);
+    /// ```
+    pub fn is_disjoint(&self, other: &BTree<T>) -> bool {
+        let mut ours = self.iter().peekable();
+        let mut theirs = other.iter().peekable();
+
+        while let (Some(a), Some(b)) = (ours.peek(), theirs.peek()) {
+            match a.cmp(b) {
+                Ordering::Less => {
+                    ours.next();
+                },
+                Ordering::Greater => {
+                    theirs.next();
+                },
+                Ordering::Equal => return false,
+            }
+        }
+
+        true
+    }
+
+    ///
+    /// Private API: merge
+    ///
+    /// Walks both trees in order, keeping each distinct value if `keep(in_self, in_other)` says
+    /// so, and builds a balanced tree from what's kept.
+    ///
+    fn merge<F>(&self, other: &BTree<T>, keep: F) -> BTree<T>
+    where
+        T: Clone,
+        F: Fn(bool, bool) -> bool,
+    {
+        let mut kept = Vec::new();
+        let mut ours = self.iter().peekable();
+        let mut theirs = other.iter().peekable();
+
+        loop {
+            let (val, in_self, in_other) = match (ours.peek(), theirs.peek()) {
+                (None, None) => break,
+                (Some(_), None) => (ours.next(), true, false),
+                (None, Some(_)) => (theirs.next(), false, true),
+                (Some(a), Some(b)) => match a.cmp(b) {
+                    Ordering::Less => (ours.next(), true, false),
+                    Ordering::Greater => (theirs.next(), false, true),
+                    Ordering::Equal => {
+                        theirs.next();
+                        (ours.next(), true, true)
+                    },
+                },
+            };
+
+            if keep(in_self, in_other) {
+                kept.extend(val.cloned());
+            }
+        }
+
+        BTree {
+            root: build_balanced(kept.len(), &mut kept.into_iter()),
+            rotations: 0,
+        }
+    }
+}
+
+#[test]
+fn test_set_algebra() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(0, 300);
+
+    let check = |tree: BTree<i32>, expected: Vec<i32>| {
+        assert_eq!(tree.iter().copied().collect::<Vec<_>>(), expected);
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree.is_height_balanced());
+    };
+
+    for _ in 0..32 {
+        let a_values = (0..rng.gen_range(0..200)).map(|_| rng.sample(distr)).collect::<Vec<_>>();
+        let b_values = (0..rng.gen_range(0..200)).map(|_| rng.sample(distr)).collect::<Vec<_>>();
+
+        // Build them lopsided, the results should come out balanced anyway.
+        let mut a = BTree::empty();
+        let mut b = BTree::empty();
+        for &val in a_values.iter() {
+            a.insert(val);
+        }
+        for &val in b_values.iter() {
+            b.insert(val);
+        }
+
+        let a_set = a_values.into_iter().collect::<BTreeSet<_>>();
+        let b_set = b_values.into_iter().collect::<BTreeSet<_>>();
+
+        check(a.union(&b), a_set.union(&b_set).copied().collect());
+        check(a.intersection(&b), a_set.intersection(&b_set).copied().collect());
+        check(a.difference(&b), a_set.difference(&b_set).copied().collect());
+        check(
+            a.symmetric_difference(&b),
+            a_set.symmetric_difference(&b_set).copied().collect(),
+        );
+
+        assert_eq!(a.is_subset(&b), a_set.is_subset(&b_set));
+        assert_eq!(a.is_disjoint(&b), a_set.is_disjoint(&b_set));
+
+        assert!(a.intersection(&b).is_subset(&a));
+        assert!(a.is_subset(&a.union(&b)));
+        assert!(a.difference(&b).is_disjoint(&b));
+    }
+
+    let empty: BTree<i32> = BTree::empty();
+    let t: BTree<i32> = (1..=5).collect();
+
+    assert_eq!(t.union(&empty), t);
+    assert_eq!(empty.union(&t), t);
+    assert!(t.intersection(&empty).is_empty());
+    assert_eq!(t.difference(&empty), t);
+    assert!(empty.is_subset(&empty));
+    assert!(empty.is_disjoint(&empty));
+    assert!(t.is_subset(&t));
+
+    // Same size but different values.
+    let u: BTree<i32> = (2..=6).collect();
+    assert!(!t.is_subset(&u));
+}
@@ -0,0 +1,351 @@
+use std::cmp::Ordering;
+
+use super::{BTree, Direction, Node};
+
+///
+/// SplayTree
+///
+/// A tree which re-arranges itself around the values you use.
+///
+/// Every `contains`, `insert` and `remove` "splays" the value it looked for up to the root with a
+/// series of rotations. Values which are looked up a lot stay near the top, so a lookup for a hot
+/// value is only a couple of steps, however big the tree is and whatever order it was built in.
+///
+/// There is no balancing as such, a splay tree can end up as lopsided as a plain `BTree`. But
+/// every splay also roughly halves the depth of the nodes along the way, so it can't stay
+/// lopsided for long: any sequence of operations costs O(log n) each on average.
+///
+/// The price is that lookups change the tree, so `contains` takes `&mut self`.
+///
+/// Underneath it is an ordinary [BTree] (sizes, heights and all), so `as_tree` gives you all of
+/// the read-only goodies like iterators, `Display`, and `validate`.
+///
+/// ```
+/// # use exercises::btree::SplayTree;
+/// let mut t = SplayTree::empty();
+/// for val in 1..=100 {
+///     t.insert(val);
+/// }
+///
+/// assert_eq!(t.contains(&50), true);
+/// assert_eq!(t.as_tree().iter().next(), Some(&1));
+/// assert_eq!(t.as_tree().to_string().starts_with("50 ->"), true);
+/// ```
+///
+#[derive(Debug, PartialEq, Clone)]
+pub struct SplayTree<T> {
+    tree: BTree<T>,
+}
+
+impl<T: Ord> Default for SplayTree<T> {
+    fn default() -> Self {
+        SplayTree::empty()
+    }
+}
+
+impl<T: Ord> SplayTree<T> {
+    pub fn new(val: T) -> SplayTree<T> {
+        SplayTree {
+            tree: BTree::new(val),
+        }
+    }
+
+    pub fn empty() -> SplayTree<T> {
+        SplayTree {
+            tree: BTree::empty(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn depth(&self) -> isize {
+        self.tree.depth()
+    }
+
+    /// The tree as it is right now, to read from without splaying anything.
+    pub fn as_tree(&self) -> &BTree<T> {
+        &self.tree
+    }
+
+    /// Give up splaying and keep the tree, in whatever shape it's in.
+    pub fn into_tree(self) -> BTree<T> {
+        self.tree
+    }
+
+    /// Is `val` in the tree?
+    ///
+    /// Either way, whatever node the search ended at is splayed up to the root, so asking again
+    /// straight away is O(1).
+    ///
+    /// ```
+    /// # use exercises::btree::SplayTree;
+    /// let mut t = SplayTree::empty();
+    /// for val in [5, 3, 8, 1] {
+    ///     t.insert(val);
+    /// }
+    ///
+    /// assert_eq!(t.contains(&3), true);
+    /// assert_eq!(t.as_tree().to_string(), "3 -> 5 -> 8\n`-> 1");
+    ///
+    /// assert_eq!(t.contains(&4), false);
+    /// ```
+    pub fn contains(&mut self, val: &T) -> bool {
+        self.splay(val);
+        self.tree.root.as_ref().is_some_and(|root| root.value == *val)
+    }
+
+    /// Insert a value, which ends up at the root.
+    ///
+    /// Splaying for `val` first brings its nearest neighbour to the root. If that isn't `val`
+    /// itself, the new node takes the root's place, with the old root on one side and the old
+    /// root's other sub-tree on the other.
+    ///
+    /// Duplicate values are not stored, but they are still splayed to the root.
+    ///
+    /// ```
+    /// # use exercises::btree::SplayTree;
+    /// let mut t = SplayTree::new(1);
+    /// t.insert(3);
+    /// t.insert(2);
+    ///
+    /// assert_eq!(t.as_tree().to_string(), "2 -> 3\n`-> 1");
+    /// ```
+    pub fn insert(&mut self, val: T) {
+        self.splay(&val);
+
+        let mut root = match self.tree.root.take() {
+            Some(root) => root,
+            None => {
+                self.tree = BTree::new(val);
+                return;
+            },
+        };
+
+        let mut node = match val.cmp(&root.value) {
+            Ordering::Equal => {
+                self.tree.root = Some(root);
+                return;
+            },
+            Ordering::Less => {
+                let mut node = Box::new(Node::new(val));
+                node.left = root.left.take();
+                root.update();
+                node.right = Some(root);
+                node
+            },
+            Ordering::Greater => {
+                let mut node = Box::new(Node::new(val));
+                node.right = root.right.take();
+                root.update();
+                node.left = Some(root);
+                node
+            },
+        };
+
+        node.update();
+        self.tree.root = Some(node);
+    }
+
+    /// Remove a value from the tree, returning whether it was there to remove.
+    ///
+    /// Once `val` has been splayed to the root we cut it out, leaving its two sub-trees. Splaying
+    /// the left one for `val` again brings its biggest value to the top, which has no right child
+    /// (everything in the left sub-tree is smaller), so the right sub-tree hangs off of it.
+    ///
+    /// ```
+    /// # use exercises::btree::SplayTree;
+    /// let mut t = SplayTree::empty();
+    /// for val in 1..=5 {
+    ///     t.insert(val);
+    /// }
+    ///
+    /// assert_eq!(t.remove(&3), true);
+    /// assert_eq!(t.remove(&3), false);
+    /// assert_eq!(t.len(), 4);
+    /// ```
+    pub fn remove(&mut self, val: &T) -> bool {
+        if !self.contains(val) {
+            return false;
+        }
+
+        let mut root = self.tree.root.take().expect("contains found the value at the root");
+
+        self.tree.root = match splay(root.left.take(), val) {
+            Some(mut left) => {
+                left.right = root.right.take();
+                left.update();
+                Some(left)
+            },
+            None => root.right.take(),
+        };
+
+        true
+    }
+
+    fn splay(&mut self, val: &T) {
+        self.tree.root = splay(self.tree.root.take(), val);
+    }
+}
+
+///
+/// Private API: splay
+///
+/// Brings `val` to the root of the tree, or the last node we passed looking for it if it isn't
+/// there.
+///
+/// Going down, we cut each node on the way off from the child we're heading to, and keep it (and
+/// the way we went) on a stack. Then we pop them off two at a time and put them back together
+/// around the node we're splaying, `x`, with its parent `p` and grandparent `g`:
+/// * zig-zig (`x` and `p` are both left children, or both right): rotate `p` up over `g`, then
+///   `x` up over `p`. Doing the top rotation first is what squashes long paths.
+/// * zig-zag (one of each): rotate `x` up over `p`, then up over `g`.
+/// * zig (`p` is the root, there is no `g`): rotate `x` up over `p`.
+///
+/// Working with owned nodes means there's no recursion, so even a vine thousands of nodes long
+/// can be splayed.
+///
+fn splay<T: Ord>(root: Option<Box<Node<T>>>, val: &T) -> Option<Box<Node<T>>> {
+    let mut x = root?;
+    let mut path = Vec::new();
+
+    loop {
+        let direction = match val.cmp(&x.value) {
+            Ordering::Equal => break,
+            Ordering::Less => Direction::Left,
+            Ordering::Greater => Direction::Right,
+        };
+
+        let child = match direction {
+            Direction::Left => x.left.take(),
+            Direction::Right => x.right.take(),
+        };
+
+        match child {
+            Some(child) => {
+                path.push((x, direction));
+                x = child;
+            },
+            None => break,
+        }
+    }
+
+    while let Some((p, p_direction)) = path.pop() {
+        x = match path.pop() {
+            None => rotate_up(p, p_direction, x),
+            Some((g, g_direction)) if g_direction == p_direction => {
+                let p = rotate_up(g, g_direction, p);
+                rotate_up(p, p_direction, x)
+            },
+            Some((g, g_direction)) => {
+                let x = rotate_up(p, p_direction, x);
+                rotate_up(g, g_direction, x)
+            },
+        };
+    }
+
+    Some(x)
+}
+
+///
+/// Private API: rotate_up
+///
+/// `child` was cut off of `parent`'s `direction` side. Puts them back together with `child` on
+/// top, and returns it.
+///
+/// ```text
+///       p            x
+///      /              \
+///     x      =>        p
+///      \              /
+///       b            b
+/// ```
+///
+fn rotate_up<T: Ord>(
+    mut parent: Box<Node<T>>,
+    direction: Direction,
+    mut child: Box<Node<T>>,
+) -> Box<Node<T>> {
+    match direction {
+        Direction::Left => {
+            parent.left = child.right.take();
+            parent.update();
+            child.right = Some(parent);
+        },
+        Direction::Right => {
+            parent.right = child.left.take();
+            parent.update();
+            child.left = Some(parent);
+        },
+    }
+    child.update();
+    child
+}
+
+#[test]
+fn test_splay_tree() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-200, 200);
+
+    let root = |t: &SplayTree<i32>| t.tree.root.as_ref().map(|root| root.value);
+
+    let mut t = SplayTree::empty();
+    let mut oracle = BTreeSet::new();
+
+    assert!(!t.contains(&0));
+    assert!(!t.remove(&0));
+
+    for _ in 0..2048 {
+        let val = rng.sample(distr);
+        match rng.gen_range(0..3) {
+            0 => {
+                t.insert(val);
+                oracle.insert(val);
+                assert_eq!(root(&t), Some(val));
+            },
+            1 => {
+                assert_eq!(t.contains(&val), oracle.contains(&val));
+                if oracle.contains(&val) {
+                    assert_eq!(root(&t), Some(val));
+                }
+            },
+            _ => assert_eq!(t.remove(&val), oracle.remove(&val)),
+        }
+
+        assert_eq!(t.as_tree().validate(), Ok(()));
+        assert_eq!(t.len(), oracle.len());
+    }
+
+    assert!(t.as_tree().iter().eq(oracle.iter()));
+
+    // Sorted inserts make a vine, and the first lookup at the bottom of it about halves its depth.
+    let mut t = SplayTree::empty();
+    for val in 0..2_000 {
+        t.insert(val);
+    }
+    assert_eq!(t.depth(), 2_000);
+
+    assert!(t.contains(&0));
+    assert_eq!(root(&t), Some(0));
+    assert!(t.depth() <= 1_002);
+    assert_eq!(t.as_tree().validate(), Ok(()));
+
+    // A hot set of values ends up near the top, however the tree was built.
+    let hot = [1_500, 20, 999];
+    for _ in 0..10 {
+        for val in hot.iter() {
+            assert!(t.contains(val));
+        }
+    }
+    let near_top = t.as_tree().level_order().take(3).copied().collect::<BTreeSet<_>>();
+    assert_eq!(near_top, hot.iter().copied().collect());
+}
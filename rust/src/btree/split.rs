@@ -0,0 +1,345 @@
+use std::cmp::Ordering;
+
+use super::{build_balanced, step_down, take_min, zip_up, BTree, Direction, Node};
+
+/// A (sub-)tree, or nothing.
+type Slot<T> = Option<Box<Node<T>>>;
+
+///
+/// Splitting and joining
+///
+/// Both are built on one operation, `join(left, pivot, right)`, which glues two trees together
+/// with a value which sits between them.
+///
+/// If the two trees are about the same height the pivot simply becomes their parent. If one is
+/// taller we walk down its inside edge (the right edge of `left`, or the left edge of `right`)
+/// until we find a sub-tree about as tall as the other tree, make the pivot the parent of those
+/// two, and rebalance on the way back up, just like `insert_balanced`. That costs the difference
+/// in heights, not the size of either tree.
+///
+/// On AVL balanced trees everything here is O(height) = O(log n) and the results are AVL balanced
+/// too. The same code works on lopsided trees built with `insert`, it just can't promise to be
+/// fast or to leave them balanced.
+///
+impl<T: Ord> BTree<T> {
+    /// Split the tree in two at `key`: this tree keeps the values less than `key`, and everything
+    /// greater than or equal to `key` is moved into the tree that is returned.
+    ///
+    /// `key` does not have to be in the tree.
+    ///
+    /// We walk down from the root towards `key`. Every node we pass goes to one side or the other
+    /// along with one of its sub-trees, and gets joined to the pieces from further down on the way
+    /// back up.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree: BTree<i32> = (1..=10).collect();
+    ///
+    /// let upper = my_tree.split_off(&4);
+    ///
+    /// assert_eq!(my_tree.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// assert_eq!(upper.iter().collect::<Vec<_>>(), vec![&4, &5, &6, &7, &8, &9, &10]);
+    /// assert_eq!(upper.is_height_balanced(), true);
+    /// ```
+    pub fn split_off(&mut self, key: &T) -> BTree<T> {
+        let (lower, upper) = split(self.root.take(), key, &mut self.rotations);
+        self.root = lower;
+        BTree {
+            root: upper,
+            rotations: 0,
+        }
+    }
+
+    /// Move every value out of `other` and into this tree, leaving `other` empty.
+    ///
+    /// When every value in one tree is smaller than every value in the other, which is the usual
+    /// case after a `split_off`, the trees are joined in O(height). We take the smallest value of
+    /// the upper tree out to use as the pivot.
+    ///
+    /// If the ranges of the trees overlap there is no way around looking at every value, so we
+    /// merge them like `union` does in O(n + m) and build a fresh balanced tree. Values already in
+    /// this tree win over equal values from `other`.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree: BTree<i32> = (1..=3).collect();
+    /// let mut other: BTree<i32> = (4..=6).collect();
+    ///
+    /// my_tree.append(&mut other);
+    ///
+    /// assert_eq!(my_tree.len(), 6);
+    /// assert_eq!(other.is_empty(), true);
+    /// assert_eq!(my_tree.is_height_balanced(), true);
+    /// ```
+    pub fn append(&mut self, other: &mut BTree<T>) {
+        let (ours, theirs) = (self.root.take(), other.root.take());
+
+        let below = |a: &Slot<T>, b: &Slot<T>| match (a, b) {
+            (Some(a), Some(b)) => max_value(a) < min_value(b),
+            _ => true,
+        };
+
+        self.root = if below(&ours, &theirs) {
+            join_all(ours, theirs, &mut self.rotations)
+        } else if below(&theirs, &ours) {
+            join_all(theirs, ours, &mut self.rotations)
+        } else {
+            merge(ours, theirs)
+        };
+    }
+
+    /// Glue two trees together, the consuming version of `append`.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree: BTree<i32> = (1..=100).collect();
+    ///
+    /// let upper = my_tree.split_off(&50);
+    /// let my_tree = upper.join(my_tree);
+    ///
+    /// assert_eq!(my_tree.iter().copied().eq(1..=100), true);
+    /// ```
+    pub fn join(mut self, mut other: BTree<T>) -> BTree<T> {
+        self.append(&mut other);
+        self
+    }
+}
+
+///
+/// Private API: split
+///
+/// Splits the (sub-)tree into the values less than `key` and the values greater than or equal to
+/// `key`.
+///
+fn split<T: Ord>(slot: Slot<T>, key: &T, rotations: &mut usize) -> (Slot<T>, Slot<T>) {
+    // On the way down each node goes to one side, along with its sub-tree on the far side of
+    // `key`. The other sub-tree is where we go next.
+    let mut lower_nodes = Vec::new();
+    let mut upper_nodes = Vec::new();
+
+    let mut current = slot;
+    while let Some(mut node) = current {
+        if node.value < *key {
+            current = node.right.take();
+            let left = node.left.take();
+            lower_nodes.push((left, node));
+        } else {
+            current = node.left.take();
+            let right = node.right.take();
+            upper_nodes.push((node, right));
+        }
+    }
+
+    // Then each piece is joined with what came out of the split below it, deepest first.
+    let mut lower = None;
+    for (left, node) in lower_nodes.into_iter().rev() {
+        lower = Some(join(left, node, lower, rotations));
+    }
+
+    let mut upper = None;
+    for (node, right) in upper_nodes.into_iter().rev() {
+        upper = Some(join(upper, node, right, rotations));
+    }
+
+    (lower, upper)
+}
+
+///
+/// Private API: join
+///
+/// Joins `left`, `pivot` and `right` into one tree, where everything in `left` is smaller than
+/// `pivot` and everything in `right` is bigger. `pivot` must have no children of its own.
+///
+/// Walking down the inside edge of the taller tree cuts the path loose as it goes, so getting
+/// back up to rebalance is a loop rather than recursion, even when the taller tree is a vine.
+///
+fn join<T: Ord>(
+    mut left: Slot<T>,
+    mut pivot: Box<Node<T>>,
+    mut right: Slot<T>,
+    rotations: &mut usize,
+) -> Box<Node<T>> {
+    let mut path = Vec::new();
+
+    while height(&left) > height(&right) + 1 {
+        let node = left.expect("a taller tree can't be empty");
+        left = step_down(&mut path, node, Direction::Right);
+    }
+
+    while height(&right) > height(&left) + 1 {
+        let node = right.expect("a taller tree can't be empty");
+        right = step_down(&mut path, node, Direction::Left);
+    }
+
+    pivot.left = left;
+    pivot.right = right;
+    pivot.update();
+
+    zip_up(&mut path, Some(pivot), true, rotations).expect("zipping up a node can't come out empty")
+}
+
+///
+/// Private API: join_all
+///
+/// Joins two trees where everything in `left` is smaller than everything in `right`, borrowing
+/// the smallest value of `right` as the pivot.
+///
+fn join_all<T: Ord>(left: Slot<T>, right: Slot<T>, rotations: &mut usize) -> Slot<T> {
+    match (left, right) {
+        (left, None) => left,
+        (None, right) => right,
+        (left, mut right) => {
+            let pivot = take_min(&mut right, true, rotations);
+            Some(join(left, pivot, right, rotations))
+        },
+    }
+}
+
+///
+/// Private API: merge
+///
+/// Merges two trees with overlapping values into a new balanced tree, keeping the value from
+/// `ours` when both have it.
+///
+fn merge<T: Ord>(ours: Slot<T>, theirs: Slot<T>) -> Slot<T> {
+    let mut ours = BTree { root: ours, rotations: 0 }.into_iter().peekable();
+    let mut theirs = BTree { root: theirs, rotations: 0 }.into_iter().peekable();
+    let mut values = Vec::with_capacity(ours.len() + theirs.len());
+
+    loop {
+        let next = match (ours.peek(), theirs.peek()) {
+            (None, None) => break,
+            (Some(_), None) => ours.next(),
+            (None, Some(_)) => theirs.next(),
+            (Some(a), Some(b)) => match a.cmp(b) {
+                Ordering::Less => ours.next(),
+                Ordering::Greater => theirs.next(),
+                Ordering::Equal => {
+                    theirs.next();
+                    ours.next()
+                },
+            },
+        };
+        values.extend(next);
+    }
+
+    build_balanced(values.len(), &mut values.into_iter())
+}
+
+fn height<T>(slot: &Slot<T>) -> isize {
+    slot.as_ref().map_or(0, |node| node.height)
+}
+
+fn min_value<T>(node: &Node<T>) -> &T {
+    let mut node = node;
+    while let Some(ref left) = node.left {
+        node = left;
+    }
+    &node.value
+}
+
+fn max_value<T>(node: &Node<T>) -> &T {
+    let mut node = node;
+    while let Some(ref right) = node.right {
+        node = right;
+    }
+    &node.value
+}
+
+#[test]
+fn test_split_off() {
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-500, 500);
+
+    for _ in 0..32 {
+        let values = (0..512).map(|_| rng.sample(distr)).collect::<BTreeSet<i32>>();
+
+        let mut t = BTree::empty();
+        for &val in values.iter() {
+            t.insert_balanced(val);
+        }
+
+        let key = rng.sample(distr);
+        let upper = t.split_off(&key);
+
+        let expected_lower = values.iter().copied().filter(|&val| val < key).collect::<Vec<_>>();
+        let expected_upper = values.iter().copied().filter(|&val| val >= key).collect::<Vec<_>>();
+
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), expected_lower);
+        assert_eq!(upper.iter().copied().collect::<Vec<_>>(), expected_upper);
+
+        for half in [&t, &upper] {
+            assert_eq!(half.validate(), Ok(()));
+            assert!(half.is_height_balanced());
+        }
+
+        // And back together again.
+        let mut upper = upper;
+        t.append(&mut upper);
+
+        assert!(upper.is_empty());
+        assert_eq!(t.iter().copied().collect::<BTreeSet<_>>(), values);
+        assert_eq!(t.validate(), Ok(()));
+        assert!(t.is_height_balanced());
+    }
+
+    // Splitting off everything, or nothing.
+    let mut t: BTree<i32> = (1..=10).collect();
+    let all = t.split_off(&0);
+    assert!(t.is_empty());
+    assert_eq!(all.len(), 10);
+
+    let mut t = all;
+    let none = t.split_off(&11);
+    assert!(none.is_empty());
+    assert_eq!(t.len(), 10);
+
+    // Lopsided trees still split correctly.
+    let mut t = BTree::empty();
+    for val in 0..100 {
+        t.insert(val);
+    }
+    let upper = t.split_off(&40);
+    assert_eq!(t.iter().copied().collect::<Vec<_>>(), (0..40).collect::<Vec<_>>());
+    assert_eq!(upper.iter().copied().collect::<Vec<_>>(), (40..100).collect::<Vec<_>>());
+    assert_eq!(t.validate(), Ok(()));
+    assert_eq!(upper.validate(), Ok(()));
+}
+
+#[test]
+fn test_append() {
+    // Very different heights, in both orders.
+    let small: BTree<i32> = (1..=3).collect();
+    let big: BTree<i32> = (100..1100).collect();
+
+    for (a, b) in [(small.clone(), big.clone()), (big.clone(), small.clone())] {
+        let t = a.join(b);
+        assert_eq!(t.len(), 1003);
+        assert_eq!(t.validate(), Ok(()));
+        assert!(t.is_height_balanced());
+        assert_eq!(t.min(), Some(&1));
+        assert_eq!(t.max(), Some(&1099));
+    }
+
+    // Overlapping ranges fall back to a merge.
+    let mut a: BTree<i32> = (0..100).step_by(2).collect();
+    let mut b: BTree<i32> = (0..100).step_by(3).collect();
+    a.append(&mut b);
+
+    let expected = (0..100).filter(|val| val % 2 == 0 || val % 3 == 0).collect::<Vec<_>>();
+    assert_eq!(a.iter().copied().collect::<Vec<_>>(), expected);
+    assert_eq!(a.validate(), Ok(()));
+    assert!(b.is_empty());
+
+    // Empty on either side.
+    let mut a: BTree<i32> = BTree::empty();
+    let mut b: BTree<i32> = (1..=5).collect();
+    a.append(&mut b);
+    assert_eq!(a.len(), 5);
+    a.append(&mut b);
+    assert_eq!(a.len(), 5);
+}
@@ -0,0 +1,310 @@
+use std::fmt;
+
+use super::{size, BTree, Node};
+
+///
+/// Which way we went at a node on the way down from the root.
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+///
+/// What was wrong with the node a [ValidationError] points at.
+///
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Violation {
+    /// The node's value is not strictly between the values of the ancestors it sits between.
+    /// For example a node in the left sub-tree of `5` which is `>= 5`.
+    OutOfOrder,
+    /// The node's cached height does not match the heights of its children.
+    Height { cached: isize, expected: isize },
+    /// The node's cached size does not match the sizes of its children.
+    Size { cached: usize, expected: usize },
+    /// The root of a [RedBlackTree](super::RedBlackTree) is red.
+    RedRoot,
+    /// A red node in a [RedBlackTree](super::RedBlackTree) has a red child.
+    RedRed,
+    /// The paths down the two sides of a [RedBlackTree](super::RedBlackTree) node pass through
+    /// different numbers of black nodes.
+    BlackHeight { left: usize, right: usize },
+}
+
+///
+/// Returned by [BTree::validate] when the tree is broken.
+///
+/// `path` is the list of turns from the root to the offending node, so an empty path means the
+/// root itself.
+///
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValidationError {
+    pub path: Vec<Direction>,
+    pub violation: Violation,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.violation {
+            Violation::OutOfOrder => write!(f, "value out of order")?,
+            Violation::Height { cached, expected } => {
+                write!(f, "cached height {} should be {}", cached, expected)?
+            },
+            Violation::Size { cached, expected } => {
+                write!(f, "cached size {} should be {}", cached, expected)?
+            },
+            Violation::RedRoot => write!(f, "red root")?,
+            Violation::RedRed => write!(f, "red node with a red child")?,
+            Violation::BlackHeight { left, right } => {
+                write!(f, "black height {} on the left but {} on the right", left, right)?
+            },
+        }
+
+        write!(f, " at root")?;
+        for direction in self.path.iter() {
+            match direction {
+                Direction::Left => write!(f, ".left")?,
+                Direction::Right => write!(f, ".right")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl<T: Ord> BTree<T> {
+    /// Checks that *every* node in the tree is balanced, not just the root.
+    ///
+    /// `balanced` only compares the depths of the root's two children, so two long vines of the
+    /// same length hanging off the root look fine to it.
+    ///
+    /// Because every node caches its own height, checking a node is O(1) and checking the whole
+    /// tree is a single O(n) walk.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(5);
+    /// for val in [4, 3, 2, 1, 6, 7, 8, 9] {
+    ///     my_tree.insert(val);
+    /// }
+    ///
+    /// assert_eq!(my_tree.balanced(), true);
+    /// assert_eq!(my_tree.is_height_balanced(), false);
+    ///
+    /// my_tree.balance();
+    ///
+    /// assert_eq!(my_tree.is_height_balanced(), true);
+    /// ```
+    pub fn is_height_balanced(&self) -> bool {
+        let mut stack: Vec<&Node<T>> = self.root.as_deref().into_iter().collect();
+
+        while let Some(node) = stack.pop() {
+            if !node.balanced() {
+                return false;
+            }
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+
+        true
+    }
+
+    /// Checks the invariants every tree has to uphold, reporting the first broken node we find.
+    ///
+    /// * Ordering: everything in a node's left sub-tree is smaller than it, everything in its
+    ///   right sub-tree is bigger.
+    ///   It is not enough to compare a node with its children, a node also has to sit between
+    ///   every ancestor it went left or right of, so we pass those bounds down as we go.
+    /// * The cached height and size of every node agree with its children.
+    ///
+    /// The walk is depth first with an explicit stack, so it works on trees of any depth.
+    /// We keep track of the path to the current node so a broken node can be pointed at.
+    ///
+    /// Every node is visited twice, like in [PostOrder](super::PostOrder). Ordering is checked on
+    /// the way down, since it needs the bounds from above. The cached values are checked on the
+    /// way back up, once both children have passed: then their cached values are known to be
+    /// right, and a bad cached value gets blamed on the node which has it, not on its parent.
+    ///
+    /// ```
+    /// # use exercises::btree::BTree;
+    /// let mut my_tree = BTree::new(5);
+    /// my_tree.insert(3);
+    /// my_tree.insert(7);
+    ///
+    /// assert_eq!(my_tree.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        // (node, how deep it is, which way we went to get here, lower bound, upper bound,
+        //  whether its children are done)
+        let mut stack = Vec::new();
+        let mut path = Vec::new();
+
+        if let Some(ref root) = self.root {
+            stack.push((&**root, 0usize, None, None, None, false));
+        }
+
+        while let Some((node, depth, direction, lower, upper, expanded)) = stack.pop() {
+            // Depth first means everything deeper than our parent is done with, so once that is
+            // trimmed off, `path` is the way to our parent (which is `depth - 1` steps long).
+            path.truncate(depth.saturating_sub(1));
+            path.extend(direction);
+
+            let error = |violation| ValidationError {
+                path: path.clone(),
+                violation,
+            };
+
+            if !expanded {
+                let above_lower = lower.is_none_or(|lower| &node.value > lower);
+                let below_upper = upper.is_none_or(|upper| &node.value < upper);
+                if !(above_lower && below_upper) {
+                    return Err(error(Violation::OutOfOrder));
+                }
+
+                stack.push((node, depth, direction, lower, upper, true));
+                if let Some(ref right) = node.right {
+                    let (lower, direction) = (Some(&node.value), Some(Direction::Right));
+                    stack.push((right, depth + 1, direction, lower, upper, false));
+                }
+                if let Some(ref left) = node.left {
+                    let (upper, direction) = (Some(&node.value), Some(Direction::Left));
+                    stack.push((left, depth + 1, direction, lower, upper, false));
+                }
+                continue;
+            }
+
+            let left = node.left.as_ref().map_or(0, |left| left.height);
+            let right = node.right.as_ref().map_or(0, |right| right.height);
+            let expected = 1 + std::cmp::max(left, right);
+            if node.height != expected {
+                return Err(error(Violation::Height {
+                    cached: node.height,
+                    expected,
+                }));
+            }
+
+            let expected = 1 + size(&node.left) + size(&node.right);
+            if node.size != expected {
+                return Err(error(Violation::Size {
+                    cached: node.size,
+                    expected,
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_validate() {
+    use rand::prelude::*;
+
+    let mut rng = thread_rng();
+
+    let distr = rand::distributions::Uniform::new_inclusive(-1000, 1000);
+
+    let mut t = BTree::empty();
+
+    assert_eq!(t.validate(), Ok(()));
+    assert!(t.is_height_balanced());
+
+    for _ in 0..1024 {
+        t.insert_balanced(rng.sample(distr));
+    }
+
+    assert_eq!(t.validate(), Ok(()));
+    assert!(t.is_height_balanced());
+
+    // A grandchild on the wrong side of the root, its parent alone can't tell.
+    //
+    //     5
+    //    / \
+    //   3   8
+    //    \
+    //     6
+    let mut t = BTree::new(5);
+    for val in [3, 8, 4] {
+        t.insert(val);
+    }
+    if let Some(ref mut root) = t.root {
+        if let Some(ref mut three) = root.left {
+            if let Some(ref mut four) = three.right {
+                four.value = 6;
+            }
+        }
+    }
+
+    let err = t.validate().unwrap_err();
+    assert_eq!(err.path, vec![Direction::Left, Direction::Right]);
+    assert_eq!(err.violation, Violation::OutOfOrder);
+    assert_eq!(err.to_string(), "value out of order at root.left.right");
+
+    // Cached values which don't add up.
+    let mut t = BTree::new(2);
+    t.insert(1);
+    if let Some(ref mut root) = t.root {
+        root.size = 5;
+    }
+
+    let err = t.validate().unwrap_err();
+    assert_eq!(err.path, vec![]);
+    assert_eq!(err.violation, Violation::Size { cached: 5, expected: 2 });
+    assert_eq!(err.to_string(), "cached size 5 should be 2 at root");
+
+    // A bad child gets the blame, not the parent which reads it.
+    let mut t = BTree::new(2);
+    t.insert(1);
+    if let Some(ref mut root) = t.root {
+        if let Some(ref mut one) = root.left {
+            one.size = 5;
+        }
+    }
+
+    let err = t.validate().unwrap_err();
+    assert_eq!(err.path, vec![Direction::Left]);
+    assert_eq!(err.violation, Violation::Size { cached: 5, expected: 1 });
+    assert_eq!(err.to_string(), "cached size 5 should be 1 at root.left");
+
+    // And the path is right after walking a whole sub-tree first.
+    let mut t = BTree::new(4);
+    for val in [2, 1, 3, 6, 5] {
+        t.insert(val);
+    }
+    if let Some(ref mut root) = t.root {
+        if let Some(ref mut six) = root.right {
+            if let Some(ref mut five) = six.left {
+                five.size = 2;
+            }
+        }
+    }
+
+    let err = t.validate().unwrap_err();
+    assert_eq!(err.path, vec![Direction::Right, Direction::Left]);
+    assert_eq!(err.violation, Violation::Size { cached: 2, expected: 1 });
+
+    let mut t = BTree::new(2);
+    t.insert(1);
+    if let Some(ref mut root) = t.root {
+        if let Some(ref mut one) = root.left {
+            one.height = 3;
+        }
+    }
+
+    let err = t.validate().unwrap_err();
+    assert_eq!(err.path, vec![Direction::Left]);
+    assert_eq!(err.violation, Violation::Height { cached: 3, expected: 1 });
+
+    // Internally lopsided trees are caught even when the root looks fine.
+    let mut t = BTree::new(5);
+    for val in [4, 3, 2, 1, 6, 7, 8, 9] {
+        t.insert(val);
+    }
+
+    assert!(t.balanced());
+    assert!(!t.is_height_balanced());
+    assert_eq!(t.validate(), Ok(()));
+}
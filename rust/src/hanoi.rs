@@ -1,3 +1,4 @@
+#[allow(dead_code)]
 pub(crate) fn run() {
     println!("Towers of Hanoi");
 }
@@ -1,14 +1,14 @@
-/// Stacks
-///
-/// Our goals with this exercise are...
-/// * Implement three stacks with one vec. [TriStack]
-/// * Design a stack which has Push, Pop, and Min, all of which operate in O(1). [ConstStack]
-/// * Implement a Queue using two Stacks. [TwoStackQueue]
-/// * Sort a stack using at most one additional stack. [StackSort]
-///
-/// NOTE: All of these implementations operate on `usize` elements.
-/// They could just as easily operate on an arbitrary T, but we operate on usize for simplicity.
-///
+//! Stacks
+//!
+//! Our goals with this exercise are...
+//! * Implement three stacks with one vec. [TriStack]
+//! * Design a stack which has Push, Pop, and Min, all of which operate in O(1). [ConstStack]
+//! * Implement a Queue using two Stacks. [TwoStackQueue]
+//! * Sort a stack using at most one additional stack. [StackSort]
+//!
+//! NOTE: All of these implementations operate on `usize` elements.
+//! They could just as easily operate on an arbitrary T, but we operate on usize for simplicity.
+//!
 
 ///
 /// TriStack
@@ -96,6 +96,7 @@
 /// Of course if I were to put this in prod I would probably implement a few ideas, benchmark them
 /// against real-world cases, and maybe even make the choice of algorithm a run-time decision.
 ///
+#[derive(Default)]
 pub struct TriStack {
     vec: Vec<Option<usize>>    
 }
@@ -143,11 +144,8 @@ impl TriStack {
 
         let val = *element;
 
-        match index {
-            Some(idx) => {
-                self.vec[idx] = None;
-            },
-            None => ()
+        if let Some(idx) = index {
+            self.vec[idx] = None;
         }
 
         val
@@ -166,22 +164,19 @@ impl TriStack {
                 self.vec.iter()
                     .enumerate()
                     .filter(|(pos, _)| pos % 3 == 0)
-                    .filter(|(_, element)| element.is_some())
-                    .last()
+                    .rfind(|(_, element)| element.is_some())
             },
             StackChoice::Second => {
                 self.vec.iter()
                     .enumerate()
                     .filter(|(pos, _)| pos % 3 == 1)
-                    .filter(|(_, element)| element.is_some())
-                    .last()
+                    .rfind(|(_, element)| element.is_some())
             },
             StackChoice::Third => {
                 self.vec.iter()
                     .enumerate()
                     .filter(|(pos, _)| pos % 3 == 2)
-                    .filter(|(_, element)| element.is_some())
-                    .last()
+                    .rfind(|(_, element)| element.is_some())
             },
         }
     }
@@ -323,14 +318,14 @@ fn test_find_top_element() {
 /// out at every turn and start doing things the hard way; then when you come up for air you'll
 /// have much better mastery over the easy stuff. That's the plan at least...
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ConstStack {
     val: Option<usize>,     // Value at top of stack
     min: Option<usize>,     // Minimum value of stack
     next: Option<Box<ConstStack>>, // Next value in stack
 }
 
-impl<'a> ConstStack {
+impl ConstStack {
     pub fn new() -> ConstStack {
         ConstStack {
             val: None,
@@ -467,10 +462,10 @@ fn test_const_stack() {
 ///   (A) has all user input, the question becomes how do we pop elements off a queue-fashion?
 ///
 /// * When the user `pop`-s something off the queue we can reverse (A) onto (B), pop desired
-/// element off, and then un-reverse the stack onto the original stack.
+///   element off, and then un-reverse the stack onto the original stack.
 ///
 /// * This is computationally inefficient, but the problem didn't specify any space of
-/// computational complexity restrictions, so it is a valid solution.
+///   computational complexity restrictions, so it is a valid solution.
 ///
 /// Here's some pseudocode for the above solution:
 ///
@@ -497,6 +492,7 @@ fn test_const_stack() {
 /// collapse the 2 because we don't care about constant multipliers just weather this is linear,
 /// sub-linear, or exponential.
 ///
+#[derive(Default)]
 pub struct TwoStackQueue {
     a: Vec<usize>,
     b: Vec<usize>,
@@ -597,6 +593,7 @@ fn test_two_stack_queue() {
 /// Look at the docs for the `StackSort::sort()` method for more information about the
 /// implementation.
 ///
+#[derive(Default)]
 pub struct StackSort {
     list: Vec<usize>,
     temp: Vec<usize>,
@@ -627,17 +624,11 @@ impl StackSort {
     }
 
     pub fn peek(&self) -> Option<usize> {
-        match self.list.last() {
-            Some(&v) => Some(v),
-            None => None,
-        }
+        self.list.last().copied()
     }
 
     fn peek_temp(&self) -> Option<usize> {
-        match self.temp.last() {
-            Some(&v) => Some(v),
-            None => None,
-        }
+        self.temp.last().copied()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -750,14 +741,14 @@ fn test_stack_sort() {
     let mut s = StackSort::new();
 
     // Check our methods work with an empty stack
-    assert_eq!(s.is_empty(), true);
+    assert!(s.is_empty());
     assert_eq!(s.peek(), None);
     assert_eq!(s.pop(), None);
 
     s.push(1);
 
     // Check is_empty and peek work
-    assert_eq!(s.is_empty(), false);
+    assert!(!s.is_empty());
     assert_eq!(s.peek(), Some(1));
 
     // Populate the stack with _unsorted_ elements